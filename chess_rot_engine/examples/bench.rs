@@ -0,0 +1,34 @@
+use std::time::Instant;
+use chess_rot_engine::chess::Epd;
+use chess_rot_engine::chess::ai::ai_strategy::{AiStrategy, Minimax};
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+use chess_rot_engine::chess::ai::search_options::SearchOptions;
+
+// Compares node counts of search with and without move ordering.
+// Usage: cargo run --release --example bench -- [bench.epd] [depth]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.get(1).map(|s| s.as_str()).unwrap_or("chess_rot_engine/resources/bench.epd");
+    let depth = args.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(3);
+    let suite = Epd::parse(&std::fs::read_to_string(path).expect("cannot read EPD file"))
+        .expect("invalid EPD file");
+
+    let unordered = SearchOptions { move_ordering: false, transposition_table: false, ..SearchOptions::new() };
+    let mut total = [0u64; 2];
+    let mut time = [0u128; 2];
+    for epd in suite.iter() {
+        let mut nodes = [0u64; 2];
+        for (i, options) in [unordered, SearchOptions::new()].into_iter().enumerate() {
+            let start = Instant::now();
+            let mut minimax = Minimax::with_options(Evaluator::new(), options, depth, 0.0);
+            minimax.find_optimal_move(&epd.board_state, &Vec::new()).expect("position has no moves");
+            nodes[i] = minimax.processed_states();
+            total[i] += nodes[i];
+            time[i] += start.elapsed().as_millis();
+        }
+        println!("{}: {} nodes unordered, {} nodes ordered", epd.id, nodes[0], nodes[1]);
+    }
+
+    println!("depth {}: {} nodes in {}ms unordered, {} nodes in {}ms ordered ({:.1}% fewer nodes)",
+             depth, total[0], time[0], total[1], time[1], 100.0 - total[1] as f64 * 100.0 / total[0] as f64);
+}
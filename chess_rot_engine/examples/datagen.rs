@@ -0,0 +1,70 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+use chess_rot_engine::chess::ai::ai_strategy::Minimax;
+use chess_rot_engine::chess::ai::evaluation_parameters::EvaluationParameters;
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+use chess_rot_engine::chess::ai::training_data::{SelfPlay, SelfPlayConfig, TrainingRecord};
+
+// Usage: cargo run --release --example datagen -- <output.bin> [games] [nodes] [threads] [params.toml]
+// Running again with the same output file plays only the games missing from it.
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let output = args.get(1).expect("missing output file").clone();
+    let games = args.get(2).and_then(|g| g.parse::<u64>().ok()).unwrap_or(10_000);
+    let nodes = args.get(3).and_then(|n| n.parse::<u64>().ok()).unwrap_or(SelfPlayConfig::DEFAULT.nodes);
+    let threads = args.get(4).and_then(|t| t.parse::<usize>().ok())
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let params = match args.get(5) {
+        Some(path) => EvaluationParameters::load(Path::new(path)).unwrap_or_else(|e| panic!("{}", e)),
+        None => EvaluationParameters::default(),
+    };
+
+    let finished = TrainingRecord::resume(Path::new(&output)).unwrap_or_else(|e| panic!("{}", e));
+    // Game number is the seed of its opening, so a resumed run plays exactly the games that are missing
+    let missing: Vec<u64> = (0..games).filter(|g| !finished.contains(g)).collect();
+    if missing.is_empty() {
+        println!("{} already holds all {} games", output, games);
+        return;
+    }
+    println!("Playing {} of {} games with {} nodes per move on {} threads", missing.len(), games, nodes, threads);
+
+    let next_game = AtomicUsize::new(0);
+    let config = SelfPlayConfig { nodes, ..SelfPlayConfig::DEFAULT };
+    let start = Instant::now();
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel::<(u64, Vec<TrainingRecord>)>();
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let next_game = &next_game;
+            let missing = &missing;
+            let params = params.clone();
+            scope.spawn(move || {
+                let mut self_play = SelfPlay::new(Minimax::new(Evaluator::with_parameters(params), 64, 0.0), config);
+                while let Some(game) = missing.get(next_game.fetch_add(1, Ordering::Relaxed)) {
+                    if sender.send((*game, self_play.play_game(*game))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Only this thread writes, games are appended in the order they finish
+        let mut played = 0;
+        let mut positions = 0;
+        for (game, records) in receiver {
+            TrainingRecord::append_game(Path::new(&output), game, &records).unwrap_or_else(|e| panic!("{}", e));
+            played += 1;
+            positions += records.len();
+            if played % 100 == 0 || played == missing.len() {
+                let seconds = start.elapsed().as_secs_f64().max(0.001);
+                println!("{} of {} games, {} new positions, {:.1} games/s", finished.len() + played, games,
+                         positions, played as f64 / seconds);
+            }
+        }
+    });
+    println!("Training data written to {}", output);
+}
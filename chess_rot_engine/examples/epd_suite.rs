@@ -0,0 +1,32 @@
+use std::time::Instant;
+use chess_rot_engine::chess::Epd;
+use chess_rot_engine::chess::ai::ai_strategy::{AiStrategy, Minimax};
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+use chess_rot_engine::chess::ai::search_options::SearchOptions;
+
+// Usage: cargo run --release --example epd_suite -- [suite.epd] [depth]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.get(1).map(|s| s.as_str()).unwrap_or("chess_rot_engine/resources/tactics.epd");
+    let depth = args.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(3);
+    let suite = Epd::parse(&std::fs::read_to_string(path).expect("cannot read EPD file"))
+        .expect("invalid EPD file");
+
+    for (name, options) in [("full width", SearchOptions::full_width()), ("selective", SearchOptions::new())] {
+        let start = Instant::now();
+        let mut solved = 0;
+        let mut nodes = 0;
+        for epd in suite.iter() {
+            let mut minimax = Minimax::with_options(Evaluator::new(), options, depth, 0.0);
+            let m = minimax.find_optimal_move(&epd.board_state, &Vec::new()).expect("position has no moves");
+            nodes += minimax.processed_states();
+            if epd.is_solved_by(m) {
+                solved += 1;
+            } else {
+                println!("{} failed with {}", epd.id, epd.board_state.to_san(m));
+            }
+        }
+        println!("{}: solved {}/{} at depth {}, {} nodes in {}ms",
+                 name, solved, suite.len(), depth, nodes, start.elapsed().as_millis());
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+use chess_rot_engine::chess::BoardState;
+use chess_rot_engine::chess::ai::evaluation_parameters::EvaluationParameters;
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+
+// Usage: cargo run --example eval_params -- dump [params.toml|params.json]
+//        cargo run --example eval_params -- explain params.toml [fen]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|s| s.as_str()) {
+        Some("dump") => match args.get(2) {
+            Some(path) => {
+                EvaluationParameters::default().save(Path::new(path)).unwrap_or_else(|e| panic!("{}", e));
+                println!("Default parameters written to {}", path);
+            }
+            None => print!("{}", EvaluationParameters::default().to_toml()),
+        },
+        Some("explain") => {
+            let path = args.get(2).expect("missing parameters file");
+            let params = EvaluationParameters::load(Path::new(path)).unwrap_or_else(|e| panic!("{}", e));
+            let board = match args.get(3) {
+                Some(fen) => BoardState::from_fen(fen).expect("invalid FEN"),
+                None => BoardState::default(),
+            };
+            println!("{}", Evaluator::with_parameters(params).explain(&board));
+        }
+        _ => println!("Usage: eval_params dump [file] | eval_params explain <file> [fen]"),
+    }
+}
@@ -0,0 +1,60 @@
+use chess_rot_engine::chess::{BoardState, Color, Epd};
+use chess_rot_engine::chess::ai::ai_strategy::{AiStrategy, Minimax};
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+use chess_rot_engine::chess::ai::search_options::SearchOptions;
+use chess_rot_engine::chess::move_provider::MoveProvider;
+
+const MAX_PLIES: usize = 200;
+
+// Plays engine with search option enabled against the same engine with option disabled.
+// Usage: cargo run --release --example self_play -- <option> [depth] [openings.epd]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let option = args.get(1).expect("missing option name, e.g. null_move_pruning");
+    let depth = args.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(3);
+    let path = args.get(3).map(|s| s.as_str()).unwrap_or("chess_rot_engine/resources/bench.epd");
+    let openings = Epd::parse(&std::fs::read_to_string(path).expect("cannot read EPD file"))
+        .expect("invalid EPD file");
+
+    let enabled = SearchOptions::new();
+    let mut disabled = SearchOptions::new();
+    assert!(disabled.set(option, false), "unknown option {}", option);
+
+    // Wins, draws and losses from the perspective of enabled option
+    let mut score = [0; 3];
+    for opening in openings.iter() {
+        for enabled_color in [Color::White, Color::Black] {
+            let result = play(opening.board_state, enabled_color, enabled, disabled, depth);
+            score[result] += 1;
+            println!("{} {} with {} as {:?}", opening.id, ["win", "draw", "loss"][result], option, enabled_color);
+        }
+    }
+
+    println!("{} enabled: +{} ={} -{}", option, score[0], score[1], score[2]);
+}
+
+fn play(mut board: BoardState, enabled_color: Color, enabled: SearchOptions, disabled: SearchOptions, depth: usize) -> usize {
+    let mut players = [
+        Minimax::with_options(Evaluator::new(), enabled, depth, 0.0),
+        Minimax::with_options(Evaluator::new(), disabled, depth, 0.0),
+    ];
+
+    for _ in 0..MAX_PLIES {
+        if MoveProvider::INSTANCE.legal_moves(&board).is_empty() {
+            if !MoveProvider::INSTANCE.is_in_check(&board) {
+                return 1;
+            }
+            return if board.on_move() == enabled_color { 2 } else { 0 };
+        }
+
+        if board.half_move_clock() >= 100 {
+            return 1;
+        }
+
+        let player = if board.on_move() == enabled_color { 0 } else { 1 };
+        let m = players[player].find_optimal_move(&board, &Vec::new()).expect("position has no moves");
+        board = board.make_move(m);
+    }
+
+    return 1;
+}
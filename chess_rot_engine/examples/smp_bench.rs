@@ -0,0 +1,32 @@
+use std::time::Instant;
+use chess_rot_engine::chess::Epd;
+use chess_rot_engine::chess::ai::ai_strategy::Minimax;
+use chess_rot_engine::chess::ai::evaluator::Evaluator;
+
+// Reports time to reach depth on the bench set with 1, 2, 4 and 8 Lazy SMP threads.
+// Usage: cargo run --release --example smp_bench -- [bench.epd] [depth]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.get(1).map(|s| s.as_str()).unwrap_or("chess_rot_engine/resources/bench.epd");
+    let depth = args.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(6);
+    let suite = Epd::parse(&std::fs::read_to_string(path).expect("cannot read EPD file"))
+        .expect("invalid EPD file");
+
+    let mut single_thread_ms = 0;
+    for threads in [1, 2, 4, 8] {
+        let start = Instant::now();
+        let mut nodes = 0;
+        for epd in suite.iter() {
+            let mut minimax = Minimax::new(Evaluator::new(), depth, 0.0);
+            minimax.set_threads(threads);
+            nodes += minimax.search(&epd.board_state).expect("position has no moves").nodes;
+        }
+
+        let time_ms = start.elapsed().as_millis().max(1);
+        if threads == 1 {
+            single_thread_ms = time_ms;
+        }
+        println!("{} threads: depth {} in {}ms, {} nodes, {} nps, speedup {:.2}",
+                 threads, depth, time_ms, nodes, nodes as u128 * 1000 / time_ms, single_thread_ms as f64 / time_ms as f64);
+    }
+}
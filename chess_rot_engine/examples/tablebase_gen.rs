@@ -0,0 +1,27 @@
+use std::path::Path;
+use std::time::Instant;
+use chess_rot_engine::chess::ai::endgame_tables::{EndgameTables, Material};
+
+// Usage: cargo run --release --example tablebase_gen -- <dir> [KQK KRK KPK KBNK ...]
+// Tables already in the directory are kept and used for captures and promotions.
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let dir = Path::new(args.get(1).expect("missing output directory"));
+    let signatures: Vec<&str> = match args.len() {
+        0..=2 => vec!["KQK", "KRK", "KPK", "KBNK"],
+        _ => args[2..].iter().map(|s| s.as_str()).collect(),
+    };
+
+    let mut tables = if dir.is_dir() { EndgameTables::load_dir(dir).unwrap_or_else(|e| panic!("{}", e)) } else { EndgameTables::new() };
+    for signature in signatures {
+        let start = Instant::now();
+        let material = Material::parse(signature).unwrap_or_else(|e| panic!("{}", e));
+        for generated in tables.generate(&material).unwrap_or_else(|e| panic!("{}", e)) {
+            let longest = tables.table(&generated).map_or(0, |t| t.longest_mate());
+            println!("{}: longest mate {} plies, {:.1}s", generated, longest, start.elapsed().as_secs_f64());
+        }
+    }
+
+    tables.save_dir(dir).unwrap_or_else(|e| panic!("{}", e));
+    println!("Tables written to {}", dir.display());
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+use std::time::Instant;
+use chess_rot_engine::chess::ai::evaluation_parameters::EvaluationParameters;
+use chess_rot_engine::chess::ai::tuning::{Tuner, TuningPosition};
+
+// Usage: cargo run --release --example texel_tune -- <positions> [tuned.toml] [epochs] [initial.toml]
+// Positions file has a quiet position with game result on every line, e.g. "<fen> [1-0]".
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let positions_path = args.get(1).expect("missing positions file");
+    let output = args.get(2).map(|s| s.as_str()).unwrap_or("tuned.toml");
+    let epochs = args.get(3).and_then(|e| e.parse::<usize>().ok()).unwrap_or(100);
+    let params = match args.get(4) {
+        Some(path) => EvaluationParameters::load(Path::new(path)).unwrap_or_else(|e| panic!("{}", e)),
+        None => EvaluationParameters::default(),
+    };
+
+    let positions = TuningPosition::parse(&std::fs::read_to_string(positions_path).expect("cannot read positions file"))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{} positions, {} values, {} threads", positions.len(), params.values().len(), threads);
+
+    let mut tuner = Tuner::new(positions, threads);
+    let k = tuner.fit_k(&params);
+    println!("K = {:.4}, initial error {:.6}", k, tuner.error(&params));
+
+    let start = Instant::now();
+    tuner.tune(&params, epochs, |epoch, error, tuned| {
+        println!("epoch {}: error {:.6} after {}s", epoch, error, start.elapsed().as_secs());
+        // Saved every epoch so an interrupted run keeps its progress
+        tuned.save(Path::new(output)).unwrap_or_else(|e| panic!("{}", e));
+    });
+    println!("Tuned parameters written to {}", output);
+}
@@ -45,9 +45,9 @@ impl BitBoard {
         BitBoard::from(Square::A5.as_bb().raw() | Square::B6.as_bb().raw() | Square::C7.as_bb().raw() | Square::D8.as_bb().raw()),
         BitBoard::from(Square::A4.as_bb().raw() | Square::B5.as_bb().raw() | Square::C6.as_bb().raw() | Square::D7.as_bb().raw() | Square::E8.as_bb().raw()),
         BitBoard::from(Square::A3.as_bb().raw() | Square::B4.as_bb().raw() | Square::C5.as_bb().raw() | Square::D6.as_bb().raw() | Square::E7.as_bb().raw() | Square::F8.as_bb().raw()),
-        BitBoard::from(Square::A2.as_bb().raw() | Square::B3.as_bb().raw() | Square::C4.as_bb().raw() | Square::D5.as_bb().raw() | Square::E6.as_bb().raw() | Square::F7.as_bb().raw() | Square::G7.as_bb().raw()),
+        BitBoard::from(Square::A2.as_bb().raw() | Square::B3.as_bb().raw() | Square::C4.as_bb().raw() | Square::D5.as_bb().raw() | Square::E6.as_bb().raw() | Square::F7.as_bb().raw() | Square::G8.as_bb().raw()),
         BitBoard::from(Square::A1.as_bb().raw() | Square::B2.as_bb().raw() | Square::C3.as_bb().raw() | Square::D4.as_bb().raw() | Square::E5.as_bb().raw() | Square::F6.as_bb().raw() | Square::G7.as_bb().raw() | Square::H8.as_bb().raw()),
-        BitBoard::from(Square::B1.as_bb().raw() | Square::C2.as_bb().raw() | Square::D3.as_bb().raw() | Square::A4.as_bb().raw() | Square::F5.as_bb().raw() | Square::G6.as_bb().raw() | Square::H7.as_bb().raw()),
+        BitBoard::from(Square::B1.as_bb().raw() | Square::C2.as_bb().raw() | Square::D3.as_bb().raw() | Square::E4.as_bb().raw() | Square::F5.as_bb().raw() | Square::G6.as_bb().raw() | Square::H7.as_bb().raw()),
         BitBoard::from(Square::C1.as_bb().raw() | Square::D2.as_bb().raw() | Square::E3.as_bb().raw() | Square::F4.as_bb().raw() | Square::G5.as_bb().raw() | Square::H6.as_bb().raw()),
         BitBoard::from(Square::D1.as_bb().raw() | Square::E2.as_bb().raw() | Square::F3.as_bb().raw() | Square::G4.as_bb().raw() | Square::H5.as_bb().raw()),
         BitBoard::from(Square::E1.as_bb().raw() | Square::F2.as_bb().raw() | Square::G3.as_bb().raw() | Square::H4.as_bb().raw()),
@@ -282,6 +282,29 @@ impl BitBoard {
         return BitBoard::from(b);
     }
 
+    /// All squares of the file, 0 is file A.
+    pub const fn file(file: usize) -> Self {
+        return BitBoard::from(BitBoard::FILE_A.value << file);
+    }
+
+    /// Files left and right of the file, without the file itself.
+    pub const fn adjacent_files(file: usize) -> Self {
+        let file = BitBoard::file(file);
+        return BitBoard::from(file.shifted_east().value | file.shifted_west().value);
+    }
+
+    /// All squares of ranks above the rank, 0 is rank 1.
+    pub const fn ranks_above(rank: usize) -> Self {
+        if rank >= 7 {
+            return BitBoard::EMPTY;
+        }
+        return BitBoard::from(!0u64 << ((rank + 1) * 8));
+    }
+
+    /// All squares of ranks below the rank, 0 is rank 1.
+    pub const fn ranks_below(rank: usize) -> Self {
+        return BitBoard::from((1u64 << (rank * 8)) - 1);
+    }
 
     fn format(&self) -> String {
         let mut str = String::new();
@@ -463,4 +486,16 @@ mod tests {
         let bb = BitBoard::from(0b0001000000000000000000000000000000000000000000000000000000000000);
         debug_assert_eq!(60, bb.msb());
     }
+
+    #[test]
+    fn test_file_and_rank_masks() {
+        assert_eq!(BitBoard::FILE_A, BitBoard::file(0));
+        assert_eq!(BitBoard::FILE_H, BitBoard::file(7));
+        assert_eq!(BitBoard::file(1), BitBoard::adjacent_files(0));
+        assert_eq!(BitBoard::from(BitBoard::file(3).raw() | BitBoard::file(5).raw()), BitBoard::adjacent_files(4));
+        assert_eq!(BitBoard::RANK_8, BitBoard::ranks_above(6));
+        assert_eq!(BitBoard::empty(), BitBoard::ranks_above(7));
+        assert_eq!(BitBoard::RANK_1, BitBoard::ranks_below(1));
+        assert_eq!(BitBoard::empty(), BitBoard::ranks_below(0));
+    }
 }
@@ -8,7 +8,7 @@ pub enum AiType {
 }
 
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct AiMoveProvider {
     minimax: Minimax,
     llm: OpenAi,
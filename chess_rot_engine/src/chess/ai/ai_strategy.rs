@@ -1,11 +1,24 @@
 use std::fmt::format;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
 use std::time::Instant;
 use crate::chess::{BoardState, Color, GameError, Move, MoveType, Piece, Square, SquareLabel};
 
 use openai_api_rust::*;
 use openai_api_rust::chat::*;
 use openai_api_rust::completions::*;
-use crate::chess::ai::evaluator::Evaluator;
+use crate::bitboard::BitBoard;
+use crate::chess::ai::evaluate::Evaluate;
+use crate::chess::ai::move_ordering::MoveOrdering;
+use crate::chess::ai::personality::Personality;
+use crate::chess::ai::search_limits::SearchLimits;
+use crate::chess::ai::search_options::SearchOptions;
+use crate::chess::ai::search_result::{PvLine, Score, SearchResult};
+use crate::chess::ai::skill_level::SkillLevel;
+use crate::chess::ai::tablebase::{TablebaseProbe, Wdl};
+use crate::chess::ai::transposition_table::{Bound, TranspositionTable};
 use crate::chess::move_provider::MoveProvider;
 
 pub trait AiStrategy {
@@ -127,10 +140,34 @@ impl AiStrategy for OpenAi {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Minimax {
-    evaluator: Evaluator,
+    evaluator: Box<dyn Evaluate>,
+    options: SearchOptions,
+    transposition_table: Arc<TranspositionTable>,
+    move_ordering: MoveOrdering,
+    // Triangular table, row for every ply holds the best line found from that ply
+    pv_table: Vec<Vec<Move>>,
+    info_sender: Option<Sender<SearchResult>>,
+    multi_pv: usize,
+    threads: usize,
+    skill_level: SkillLevel,
+    // State of the random generator used by weaker skill levels, advanced every search
+    skill_seed: u64,
+    contempt: i32,
+    tablebase: Option<TablebaseProbe>,
+    // Keys of positions played before the searched one, oldest first
+    history: Vec<u64>,
+    // History followed by positions on the searched line, indexed by history length + ply
+    position_keys: Vec<u64>,
+    // Stops the running search and signals helper threads to finish once the main thread completes it,
+    // replaced after every search so a handle stops only one search
+    stop: Arc<AtomicBool>,
+    // Hard limits of the running search checked inside of the tree, only by the main thread
+    deadline: Option<Instant>,
+    node_limit: Option<u64>,
     processed_states_counter: u64,
+    seldepth: usize,
     max_depth: usize,
     max_time: f32,
 }
@@ -138,60 +175,519 @@ pub struct Minimax {
 impl Minimax {
     const MAX: i32 = 500000;
     const MIN: i32 = -500000;
+    const MATE: i32 = 100000;
+    // Score of tablebase wins, above any evaluation and below mate scores
+    const TABLEBASE_WIN: i32 = 20000;
+    const MAX_PLY: usize = 128;
+    // Safety margin on top of captured piece value used for delta pruning
+    const DELTA_MARGIN: i32 = 200;
+    const NULL_MOVE_MIN_DEPTH: usize = 3;
+    const NULL_MOVE_REDUCTION: usize = 2;
+    const LATE_MOVE_MIN_DEPTH: usize = 3;
+    // Moves searched before reductions start, leaves room for hash move, captures and killers
+    const LATE_MOVE_INDEX: usize = 4;
+    const REVERSE_FUTILITY_DEPTH: usize = 3;
+    const REVERSE_FUTILITY_MARGIN: i32 = 120;
+    // Margins for futility pruning indexed by remaining depth - 1
+    const FUTILITY_MARGINS: [i32; 2] = [200, 500];
+    const TIME_CHECK_INTERVAL: u64 = 1024;
+    const ASPIRATION_MIN_DEPTH: usize = 4;
+    const ASPIRATION_WINDOW: i32 = 25;
+    // Window wider than this is replaced by full window
+    const ASPIRATION_MAX_WINDOW: i32 = 1000;
+
+    pub fn new<E: Evaluate + 'static>(evaluator: E, max_depth: usize, max_time: f32) -> Self {
+        return Self::with_options(evaluator, SearchOptions::new(), max_depth, max_time);
+    }
+
+    pub fn with_options<E: Evaluate + 'static>(evaluator: E, options: SearchOptions, max_depth: usize, max_time: f32) -> Self {
+        let table_size = if options.transposition_table { TranspositionTable::DEFAULT_SIZE_MB } else { 0 };
+        Self {
+            evaluator: Box::new(evaluator),
+            options,
+            transposition_table: Arc::new(TranspositionTable::new(table_size)),
+            move_ordering: MoveOrdering::new(Self::MAX_PLY),
+            pv_table: vec![Vec::new(); Self::MAX_PLY + 2],
+            info_sender: None,
+            multi_pv: 1,
+            threads: 1,
+            skill_level: SkillLevel::full_strength(),
+            skill_seed: 0,
+            contempt: 0,
+            tablebase: None,
+            history: Vec::new(),
+            position_keys: Vec::with_capacity(Self::MAX_PLY),
+            stop: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            node_limit: None,
+            processed_states_counter: 0,
+            seldepth: 0,
+            max_depth,
+            max_time,
+        }
+    }
+
+    pub fn processed_states(&self) -> u64 {
+        return self.processed_states_counter;
+    }
+
+    /// Streams result of every finished iteration to the receiver.
+    pub fn set_info_sender(&mut self, sender: Sender<SearchResult>) {
+        self.info_sender = Some(sender);
+    }
+
+    /// Number of best root moves searched with their own line, clamped to number of legal moves.
+    pub fn set_multi_pv(&mut self, lines: usize) {
+        self.multi_pv = lines.max(1);
+    }
+
+    /// Number of threads used by Lazy SMP, single thread keeps the search deterministic.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
 
-    pub const fn new(evaluator: Evaluator, max_depth: usize, max_time: f32) -> Self {
-        Self { evaluator, processed_states_counter: 0, max_depth, max_time }
+    /// Limits strength of the engine, seed makes choices of weaker levels repeatable.
+    pub fn set_skill_level(&mut self, skill_level: SkillLevel, seed: u64) {
+        self.skill_level = skill_level;
+        // xorshift gets stuck on zero
+        self.skill_seed = seed.max(1);
+    }
+
+    pub fn skill_level(&self) -> SkillLevel {
+        return self.skill_level;
+    }
+
+    /// Centipawns the engine gives up by accepting a draw, negative values make it seek draws.
+    pub fn set_contempt(&mut self, contempt: i32) {
+        self.contempt = contempt;
+    }
+
+    /// Positions of the game before the searched one, oldest first, so repetitions are scored as draws.
+    pub fn set_history(&mut self, history: Vec<u64>) {
+        self.history = history;
+    }
+
+    pub fn push_history(&mut self, key: u64) {
+        self.history.push(key);
+    }
+
+    /// Endgame tables used to pick root moves and to end search in positions with few pieces.
+    pub fn set_tablebase(&mut self, tablebase: Option<TablebaseProbe>) {
+        self.tablebase = tablebase;
+    }
+
+    pub fn set_evaluator(&mut self, evaluator: Box<dyn Evaluate>) {
+        self.evaluator = evaluator;
+    }
+
+    pub fn evaluator(&self) -> &dyn Evaluate {
+        return self.evaluator.as_ref();
+    }
+
+    /// Evaluation weights and contempt of the personality, replaces the evaluator by the hand-crafted one.
+    pub fn set_personality(&mut self, personality: &Personality) {
+        self.evaluator = Box::new(personality.evaluator());
+        self.contempt = personality.contempt;
+    }
+
+    /// Flag that interrupts the running or next search when set, search still returns the best move
+    /// found so far. Flag set before the search starts is kept, every search gets a new flag once it returns.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        return self.stop.clone();
+    }
+
+    fn stopped(&self) -> bool {
+        return self.stop.load(Ordering::Relaxed);
+    }
+
+    fn check_limits(&mut self) {
+        if let Some(limit) = self.node_limit {
+            if self.processed_states_counter >= limit {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        }
+
+        // Reading clock is slow compared to node, so it is checked only once in a while
+        if self.processed_states_counter % Self::TIME_CHECK_INTERVAL == 0 {
+            if let Some(deadline) = self.deadline {
+                if Instant::now() >= deadline {
+                    self.stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn to_score(value: i32) -> Score {
+        return if value.abs() > Self::MATE - Self::MAX_PLY as i32 {
+            let plies = Self::MATE - value.abs();
+            Score::Mate(value.signum() * (plies + 1) / 2)
+        } else {
+            Score::Centipawns(value)
+        };
+    }
+
+    fn update_pv(&mut self, ply: usize, m: Move) {
+        let (current, deeper) = self.pv_table.split_at_mut(ply + 1);
+        current[ply].clear();
+        current[ply].push(m);
+        current[ply].extend_from_slice(&deeper[0]);
+    }
+
+    /// Evaluation from the perspective of the color on move.
+    fn evaluate(&self, board_state: &BoardState) -> i32 {
+        let evaluation = self.evaluator.evaluate(board_state) * board_state.color_on_move.factor();
+        return evaluation + self.eval_noise(board_state);
+    }
+
+    /// Noise of weaker skill levels, derived from the position so the same position
+    /// keeps its evaluation during one search.
+    fn eval_noise(&self, board_state: &BoardState) -> i32 {
+        let noise = self.skill_level.eval_noise();
+        if noise == 0 {
+            return 0;
+        }
+
+        let hash = (board_state.zobrist_key() ^ self.skill_seed).wrapping_mul(0x9E3779B97F4A7C15);
+        return ((hash >> 32) % (2 * noise as u64 + 1)) as i32 - noise;
+    }
+
+    /// Score of position without legal moves, mates closer to the root are preferred.
+    fn terminal_score(&self, board_state: &BoardState, ply: usize) -> i32 {
+        return if MoveProvider::INSTANCE.is_in_check(board_state) {
+            -Self::MATE + ply as i32
+        } else {
+            self.draw_score(ply)
+        };
+    }
+
+    /// Fifty-move rule or position repeated since the last irreversible move.
+    /// Single repetition inside of the search is enough, the line could be repeated again.
+    fn is_draw(&self, board_state: &BoardState, key: u64) -> bool {
+        let clock = board_state.half_move_clock() as usize;
+        if clock >= 100 {
+            let mated = MoveProvider::INSTANCE.is_in_check(board_state)
+                && MoveProvider::INSTANCE.legal_moves(board_state).is_empty();
+            return !mated;
+        }
+
+        // Same side is on move only every other position
+        return self.position_keys.iter().rev().take(clock).skip(1).step_by(2).any(|k| *k == key);
+    }
+
+    /// Draw is worth minus contempt for the side on move at the root.
+    fn draw_score(&self, ply: usize) -> i32 {
+        return if ply % 2 == 0 { -self.contempt } else { self.contempt };
+    }
+
+    /// Score of the position known from tablebases, faster wins are preferred like with mates.
+    fn probe_tablebase(&self, board_state: &BoardState, depth: usize, ply: usize) -> Option<i32> {
+        let probe = self.tablebase.as_ref()?;
+        if ply == 0 || depth < probe.probe_depth || !probe.can_probe(board_state) {
+            return None;
+        }
+
+        return probe.tablebase.probe_wdl(board_state).map(|wdl| match wdl {
+            Wdl::Win => Self::TABLEBASE_WIN - ply as i32,
+            Wdl::Draw => self.draw_score(ply),
+            Wdl::Loss => -Self::TABLEBASE_WIN + ply as i32,
+        });
+    }
+
+    // Mate and tablebase scores are stored relative to the position instead of the root
+    fn score_to_tt(score: i32, ply: usize) -> i32 {
+        return if score > Self::TABLEBASE_WIN - Self::MAX_PLY as i32 {
+            score + ply as i32
+        } else if score < -Self::TABLEBASE_WIN + Self::MAX_PLY as i32 {
+            score - ply as i32
+        } else {
+            score
+        };
+    }
+
+    fn score_from_tt(score: i32, ply: usize) -> i32 {
+        return if score > Self::TABLEBASE_WIN - Self::MAX_PLY as i32 {
+            score - ply as i32
+        } else if score < -Self::TABLEBASE_WIN + Self::MAX_PLY as i32 {
+            score + ply as i32
+        } else {
+            score
+        };
     }
 
-    fn min(&mut self, board_state: &BoardState, depth: usize, alpha: i32, beta: i32) -> i32 {
-        let legal_moves = MoveProvider::INSTANCE.legal_moves(board_state);
-        if depth == 0 {
+    fn negamax(&mut self, board_state: &BoardState, depth: usize, ply: usize, alpha: i32, beta: i32, previous: Option<Move>) -> i32 {
+        self.check_limits();
+        if self.stopped() {
+            return 0;
+        }
+
+        self.pv_table[ply].clear();
+        self.seldepth = self.seldepth.max(ply);
+        let key = board_state.zobrist_key();
+        self.position_keys.truncate(self.history.len() + ply);
+        if ply > 0 && self.is_draw(board_state, key) {
+            return self.draw_score(ply);
+        }
+        self.position_keys.push(key);
+
+        let in_check = MoveProvider::INSTANCE.is_in_check(board_state);
+        let depth = if in_check && self.options.check_extensions && ply < Self::MAX_PLY / 2 { depth + 1 } else { depth };
+        if depth == 0 || ply >= Self::MAX_PLY {
+            if self.options.quiescence {
+                return self.quiescence(board_state, ply, alpha, beta);
+            }
             self.processed_states_counter += 1;
-            return self.evaluator.evaluate(board_state, &Vec::new(), depth);
+            return self.evaluate(board_state);
         }
 
-        let mut worst = Self::MAX;
-        let mut _beta = beta;
-        for m in legal_moves {
+        // Even mate found here could not beat mate already found closer to the root
+        let (alpha, beta) = if self.options.mate_distance_pruning {
+            ((-Self::MATE + ply as i32).max(alpha), (Self::MATE - ply as i32 - 1).min(beta))
+        } else {
+            (alpha, beta)
+        };
+        if alpha >= beta {
+            return alpha;
+        }
+
+        let pv_node = beta - alpha > 1;
+        let mut hash_move = None;
+        if self.options.transposition_table {
+            if let Some(entry) = self.transposition_table.probe(key) {
+                hash_move = entry.best_move;
+                let score = Self::score_from_tt(entry.score, ply);
+                if ply > 0 && entry.depth >= depth {
+                    match entry.bound {
+                        Bound::Exact => return score,
+                        Bound::Lower if score >= beta => return score,
+                        Bound::Upper if score <= alpha => return score,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if let Some(score) = self.probe_tablebase(board_state, depth, ply) {
+            // Draws carry contempt of the root side, only wins and losses are stored
+            if self.options.transposition_table && score.abs() > Self::TABLEBASE_WIN - Self::MAX_PLY as i32 {
+                self.transposition_table.store(key, None, depth, Self::score_to_tt(score, ply), Bound::Exact);
+            }
+            return score;
+        }
+
+        let selective = !in_check && !pv_node && ply > 0;
+        let static_eval = if selective { self.evaluate(board_state) } else { 0 };
+
+        // Position is so good that even a margin per remaining ply keeps it above beta
+        if selective
+            && self.options.reverse_futility_pruning
+            && depth <= Self::REVERSE_FUTILITY_DEPTH
+            && beta.abs() < Self::MATE - Self::MAX_PLY as i32
+            && static_eval - Self::REVERSE_FUTILITY_MARGIN * depth as i32 >= beta {
+            return static_eval;
+        }
+
+        // Giving opponent a free move still fails high, skipped without pieces because of zugzwang
+        if selective
+            && self.options.null_move_pruning
+            && depth >= Self::NULL_MOVE_MIN_DEPTH
+            && previous.is_some()
+            && static_eval >= beta
+            && beta.abs() < Self::MATE - Self::MAX_PLY as i32
+            && board_state.has_non_pawn_material(board_state.color_on_move) {
+            let reduction = Self::NULL_MOVE_REDUCTION + depth / 4;
+            let null_state = board_state.make_null_move();
+            self.evaluator.push(board_state, &null_state);
+            let score = -self.negamax(&null_state, depth.saturating_sub(reduction + 1), ply + 1, -beta, -beta + 1, None);
+            self.evaluator.pop();
+            if self.stopped() {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+        }
+
+        let mut legal_moves = MoveProvider::INSTANCE.legal_moves(board_state);
+        if legal_moves.is_empty() {
+            return self.terminal_score(board_state, ply);
+        }
+
+        if self.options.move_ordering {
+            legal_moves = self.move_ordering.order_moves(board_state, legal_moves, hash_move, ply, previous);
+        }
+
+        let futile = selective
+            && self.options.futility_pruning
+            && depth <= Self::FUTILITY_MARGINS.len()
+            && alpha.abs() < Self::MATE - Self::MAX_PLY as i32
+            && static_eval + Self::FUTILITY_MARGINS[depth - 1] <= alpha;
+
+        let mut best = Self::MIN;
+        let mut best_move = None;
+        let mut _alpha = alpha;
+        let mut searched_quiets = Vec::new();
+        for (index, m) in legal_moves.into_iter().enumerate() {
             let next_state = board_state.make_move(m);
-            let current = self.max(&next_state, depth - 1, alpha, _beta);
-            if current <= worst {
-                worst = current;
+            let quiet = !board_state.is_capture(m) && !board_state.is_promotion(m);
+            let gives_check = quiet && MoveProvider::INSTANCE.is_in_check(&next_state);
+
+            // Quiet moves near the horizon can not bring the score back to alpha
+            if futile && quiet && !gives_check && index > 0 {
+                continue;
             }
 
-            if current <= beta {
-                _beta = current;
+            let reduction = if self.options.late_move_reductions
+                && depth >= Self::LATE_MOVE_MIN_DEPTH
+                && index >= Self::LATE_MOVE_INDEX
+                && quiet
+                && !in_check
+                && !gives_check {
+                (1 + (index >= Self::LATE_MOVE_INDEX * 2) as usize).min(depth - 2)
+            } else {
+                0
+            };
+
+            self.evaluator.push(board_state, &next_state);
+            let current = if index == 0 {
+                -self.negamax(&next_state, depth - 1, ply + 1, -beta, -_alpha, Some(m))
+            } else {
+                // Later moves are searched with null window and repeated with full one if they beat alpha
+                let pvs = self.options.principal_variation_search;
+                let window_beta = if pvs { _alpha + 1 } else { beta };
+                let mut score = -self.negamax(&next_state, depth - 1 - reduction, ply + 1, -window_beta, -_alpha, Some(m));
+                if score > _alpha && reduction > 0 {
+                    score = -self.negamax(&next_state, depth - 1, ply + 1, -window_beta, -_alpha, Some(m));
+                }
+                if pvs && score > _alpha && score < beta {
+                    score = -self.negamax(&next_state, depth - 1, ply + 1, -beta, -_alpha, Some(m));
+                }
+                score
+            };
+            self.evaluator.pop();
+
+            // Unfinished search must not get into the transposition table
+            if self.stopped() {
+                return 0;
+            }
+
+            if current > best {
+                best = current;
+                best_move = Some(m);
             }
 
-            if _beta < alpha {
+            if current > _alpha {
+                _alpha = current;
+                self.update_pv(ply, m);
+            }
+
+            if _alpha >= beta {
+                if self.options.move_ordering && quiet {
+                    self.move_ordering.update_quiet(board_state.color_on_move, m, &searched_quiets, ply, depth, previous);
+                }
                 break;
             }
+
+            if quiet {
+                searched_quiets.push(m);
+            }
         }
 
-        return worst;
+        if self.options.transposition_table {
+            let bound = if best >= beta {
+                Bound::Lower
+            } else if best > alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            let stored_move = if bound == Bound::Upper { None } else { best_move };
+            self.transposition_table.store(key, stored_move, depth, Self::score_to_tt(best, ply), bound);
+        }
+
+        return best;
     }
 
-    fn max(&mut self, board_state: &BoardState, depth: usize, alpha: i32, beta: i32) -> i32 {
-        let legal_moves = MoveProvider::INSTANCE.legal_moves(board_state);
-        if depth == 0 {
-            self.processed_states_counter += 1;
-            return self.evaluator.evaluate(board_state, &Vec::new(), depth);
+    /// Searches captures and promotions (or all evasions when in check) until the position is quiet,
+    /// so the static evaluation is never taken in the middle of an exchange.
+    fn quiescence(&mut self, board_state: &BoardState, ply: usize, alpha: i32, beta: i32) -> i32 {
+        self.processed_states_counter += 1;
+        self.check_limits();
+        self.pv_table[ply].clear();
+        self.seldepth = self.seldepth.max(ply);
+        let in_check = MoveProvider::INSTANCE.is_in_check(board_state);
+        if ply >= Self::MAX_PLY {
+            return self.evaluate(board_state);
         }
 
         let mut best = Self::MIN;
+        let mut stand_pat = Self::MIN;
         let mut _alpha = alpha;
+        if !in_check {
+            stand_pat = self.evaluate(board_state);
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+
+            // Even winning a queen would not raise alpha, unless a pawn is about to promote
+            let promotion_rank = match board_state.color_on_move {
+                Color::White => BitBoard::RANK_8.shifted_south(),
+                Color::Black => BitBoard::RANK_1.shifted_north(),
+            };
+            let pawns = board_state.pieces[board_state.color_on_move.index()][Piece::Pawn.index()];
+            if self.options.delta_pruning
+                && (pawns & promotion_rank).is_empty()
+                && stand_pat + Piece::Queen.value() as i32 + Self::DELTA_MARGIN < _alpha {
+                return stand_pat;
+            }
+
+            best = stand_pat;
+            if stand_pat > _alpha {
+                _alpha = stand_pat;
+            }
+        }
+
+        let mut legal_moves = MoveProvider::INSTANCE.legal_moves(board_state);
+        if legal_moves.is_empty() && in_check {
+            return self.terminal_score(board_state, ply);
+        }
+
+        if self.options.move_ordering {
+            legal_moves = MoveOrdering::order_captures(board_state, legal_moves);
+        }
+
         for m in legal_moves {
+            if !in_check {
+                let captured = board_state.captured_piece(m);
+                let promotion = board_state.is_promotion(m);
+                if captured.is_none() && !(promotion && m.get_target_piece() == Piece::Queen) {
+                    continue;
+                }
+
+                if self.options.delta_pruning
+                    && !promotion
+                    && stand_pat + captured.map_or(0, |p| p.value() as i32) + Self::DELTA_MARGIN <= _alpha {
+                    continue;
+                }
+            }
+
             let next_state = board_state.make_move(m);
-            let current = self.min(&next_state, depth - 1, _alpha, beta);
-            if current >= best {
+            self.evaluator.push(board_state, &next_state);
+            let current = -self.quiescence(&next_state, ply + 1, -beta, -_alpha);
+            self.evaluator.pop();
+            if self.stopped() {
+                return 0;
+            }
+
+            if current > best {
                 best = current;
             }
 
-            if current >= alpha {
+            if current > _alpha {
                 _alpha = current;
+                self.update_pv(ply, m);
             }
 
-            if beta < _alpha {
+            if _alpha >= beta {
                 break;
             }
         }
@@ -200,49 +696,556 @@ impl Minimax {
     }
 }
 
-impl AiStrategy for Minimax {
-    fn find_optimal_move(&mut self, board: &BoardState, legal_moves: &Vec<Move>) -> Result<Move, GameError> {
-        let mut best = Self::MIN;
-        let mut worst = Self::MAX;
-        let mut current = 0;
-        let starting_moves = MoveProvider::INSTANCE.legal_moves(board);
-        let move_count = starting_moves.len();
-        let counter = 1;
-        let mut best_move = None;
+impl Minimax {
+    /// Searches position up to the configured depth and time.
+    pub fn search(&mut self, board: &BoardState) -> Result<SearchResult, GameError> {
+        let limits = SearchLimits {
+            depth: Some(self.max_depth.max(1)),
+            movetime: if self.max_time > 0.0 { Some((self.max_time * 1000.0) as u64) } else { None },
+            ..SearchLimits::default()
+        };
+        return self.search_with_limits(board, &limits);
+    }
+
+    /// Searches position within the limits, weaker skill levels tighten them and may play
+    /// one of the worse lines instead of the best one.
+    pub fn search_with_limits(&mut self, board: &BoardState, limits: &SearchLimits) -> Result<SearchResult, GameError> {
+        if self.skill_level.is_full_strength() {
+            return self.run_search(board, limits);
+        }
+
+        let skill_limits = self.skill_level.limits();
+        let limits = SearchLimits {
+            depth: Self::tighter(limits.depth, skill_limits.depth),
+            nodes: Self::tighter(limits.nodes, skill_limits.nodes),
+            ..*limits
+        };
+        self.skill_seed = SkillLevel::next_random(self.skill_seed);
+        let multi_pv = self.multi_pv;
+        self.multi_pv = multi_pv.max(self.skill_level.multi_pv());
+        let result = self.run_search(board, &limits);
+        self.multi_pv = multi_pv;
+
+        let mut result = result?;
+        let chosen = self.skill_level.pick_move(&result, self.skill_seed);
+        if let Some(index) = result.lines.iter().position(|l| l.pv[0] == chosen) {
+            let line = result.lines.remove(index);
+            result.best_move = chosen;
+            result.score = line.score;
+            result.pv = line.pv.clone();
+            result.lines.insert(0, line);
+        }
+        result.lines.truncate(multi_pv);
+        return Ok(result);
+    }
+
+    fn tighter<T: Ord>(limit: Option<T>, other: Option<T>) -> Option<T> {
+        return match (limit, other) {
+            (Some(limit), Some(other)) => Some(limit.min(other)),
+            (limit, other) => limit.or(other),
+        };
+    }
+
+    /// Searches position with all configured threads and returns result of the main thread.
+    /// Reference: https://www.chessprogramming.org/Lazy_SMP
+    fn run_search(&mut self, board: &BoardState, limits: &SearchLimits) -> Result<SearchResult, GameError> {
         let start = Instant::now();
-        for m in starting_moves {
-            println!("Looking for move {} ({} to {}) out of {}", counter, m.get_from(), m.get_to(), move_count);
-            let next_state = board.make_move(m);
-            current = match board.color_on_move {
-                Color::White => self.min(&next_state, self.max_depth - 1, best, worst),
-                Color::Black => self.max(&next_state, self.max_depth - 1, best, worst),
+        self.processed_states_counter = 0;
+        self.seldepth = 0;
+        self.move_ordering.clear();
+        let budget = limits.time_budget(board.color_on_move);
+        self.deadline = budget.map(|b| start + b);
+        self.node_limit = limits.nodes;
+        let result = if self.threads == 1 {
+            self.iterative_deepening(board, limits, start)
+        } else {
+            self.smp_search(board, limits, start)
+        };
+        // Stop requested for this search must not end the next one
+        self.stop = Arc::new(AtomicBool::new(false));
+        return result;
+    }
+
+    fn smp_search(&mut self, board: &BoardState, limits: &SearchLimits, start: Instant) -> Result<SearchResult, GameError> {
+        return thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|id| {
+                    let mut helper = self.helper();
+                    scope.spawn(move || helper.helper_search(board, id))
+                })
+                .collect();
+
+            let mut result = self.iterative_deepening(board, limits, start);
+            self.stop.store(true, Ordering::Relaxed);
+            for helper in helpers {
+                self.processed_states_counter += helper.join().unwrap_or(0);
+            }
+
+            if let Ok(result) = result.as_mut() {
+                result.nodes = self.processed_states_counter;
+                result.nps = (self.processed_states_counter as u128 * 1000 / result.time_ms.max(1)) as u64;
+            }
+            result
+        });
+    }
+
+    fn helper(&self) -> Minimax {
+        return Minimax {
+            move_ordering: MoveOrdering::new(Self::MAX_PLY),
+            pv_table: vec![Vec::new(); Self::MAX_PLY + 2],
+            info_sender: None,
+            multi_pv: 1,
+            threads: 1,
+            deadline: None,
+            node_limit: None,
+            processed_states_counter: 0,
+            seldepth: 0,
+            ..self.clone()
+        };
+    }
+
+    /// Searches deeper and deeper until the main thread finishes, only to fill the shared
+    /// transposition table. Odd helpers skip depths so threads do not search the same trees.
+    fn helper_search(&mut self, board: &BoardState, id: usize) -> u64 {
+        let mut root_moves = MoveProvider::INSTANCE.legal_moves(board);
+        let mut best_move = None;
+        let mut depth = 1 + id % 2;
+        while !self.stopped() && depth < Self::MAX_PLY / 2 && !root_moves.is_empty() {
+            if self.options.move_ordering {
+                root_moves = self.move_ordering.order_moves(board, root_moves, best_move, 0, None);
+            }
+            best_move = self.search_root(board, &root_moves, depth, Self::MIN, Self::MAX).map(|(_, line)| line.pv[0]);
+            depth += 1;
+        }
+        return self.processed_states_counter;
+    }
+
+    /// Iterative deepening, every iteration searches best moves of the previous one first.
+    fn iterative_deepening(&mut self, board: &BoardState, limits: &SearchLimits, start: Instant) -> Result<SearchResult, GameError> {
+        let mut root_moves = MoveProvider::INSTANCE.legal_moves(board);
+        if root_moves.is_empty() {
+            return Err(GameError::NoPossibleMoveError);
+        }
+        // Search only decides between the moves that keep the tablebase result
+        if let Some(moves) = self.tablebase.as_ref().and_then(|t| t.root_moves(board, &root_moves)) {
+            root_moves = moves;
+        }
+
+        let budget = limits.time_budget(board.color_on_move);
+        let max_depth = limits.depth.unwrap_or(Self::MAX_PLY / 2).clamp(1, Self::MAX_PLY / 2);
+        let mut result: Option<SearchResult> = None;
+        let mut previous_scores: Vec<i32> = Vec::new();
+        let first_depth = if self.options.move_ordering || self.options.transposition_table { 1 } else { max_depth };
+        for depth in first_depth..=max_depth {
+            if self.options.move_ordering {
+                root_moves = self.move_ordering.order_moves(board, root_moves, result.as_ref().map(|r| r.best_move), 0, None);
+            }
+
+            if let Some(previous) = &result {
+                root_moves.sort_by_key(|m| previous.lines.iter().position(|l| l.pv[0] == *m).unwrap_or(usize::MAX));
+            }
+
+            // Every line is searched without root moves of the better lines
+            let mut lines: Vec<PvLine> = Vec::new();
+            let mut scores: Vec<i32> = Vec::new();
+            for index in 0..self.multi_pv.clamp(1, root_moves.len()) {
+                let remaining: Vec<Move> = root_moves.iter()
+                    .filter(|m| !lines.iter().any(|l| l.pv[0] == **m))
+                    .copied()
+                    .collect();
+                match self.aspiration_search(board, &remaining, depth, previous_scores.get(index).copied()) {
+                    Some((score, line)) => {
+                        scores.push(score);
+                        lines.push(line);
+                    }
+                    None => break,
+                }
+                if self.stopped() {
+                    break;
+                }
+            }
+
+            // Interrupted iteration is used only when there is nothing better
+            if self.stopped() && result.is_some() {
+                break;
+            }
+
+            if lines.is_empty() {
+                let score = Self::to_score(self.evaluate(board));
+                lines.push(PvLine { score, pv: vec![root_moves[0]] });
+            }
+
+            let time_ms = start.elapsed().as_millis();
+            let iteration = SearchResult {
+                best_move: lines[0].pv[0],
+                score: lines[0].score,
+                pv: lines[0].pv.clone(),
+                lines,
+                depth,
+                seldepth: self.seldepth,
+                nodes: self.processed_states_counter,
+                nps: (self.processed_states_counter as u128 * 1000 / time_ms.max(1)) as u64,
+                hashfull: self.transposition_table.hashfull(),
+                time_ms,
             };
 
-            if board.color_on_move == Color::White && current >= best {
-                best_move = Some(m);
-                best = current;
-            } else if board.color_on_move == Color::Black && current <= best {
-                best_move = Some(m);
-                worst = current;
+            if let Some(sender) = &self.info_sender {
+                // receiver may already be gone, search result is returned anyway
+                let _ = sender.send(iteration.clone());
+            }
+
+            let mate_found = match (iteration.score, limits.mate) {
+                (Score::Mate(moves), Some(mate)) => moves > 0 && moves as u32 <= mate,
+                _ => false,
+            };
+            // Next iteration takes longer than all previous ones together, so it would not finish anyway
+            let out_of_time = budget.map_or(false, |b| start.elapsed() * 2 > b);
+            result = Some(iteration);
+            previous_scores = scores;
+            if self.stopped() || mate_found || out_of_time {
+                break;
+            }
+        }
+
+        return result.ok_or(GameError::NoPossibleMoveError);
+    }
+
+    /// Searches root in narrow window around score of the previous iteration, window is widened
+    /// on the failing side until the score falls inside of it.
+    /// Reference: https://www.chessprogramming.org/Aspiration_Windows
+    fn aspiration_search(&mut self, board: &BoardState, root_moves: &[Move], depth: usize, previous: Option<i32>) -> Option<(i32, PvLine)> {
+        let mut delta = Self::ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match previous {
+            Some(score) if self.options.aspiration_windows
+                && depth >= Self::ASPIRATION_MIN_DEPTH
+                && score.abs() < Self::MATE - Self::MAX_PLY as i32 => (score - delta, score + delta),
+            _ => (Self::MIN, Self::MAX),
+        };
+
+        loop {
+            let (score, line) = self.search_root(board, root_moves, depth, alpha, beta)?;
+            if self.stopped() {
+                return Some((score, line));
+            }
+
+            delta *= 2;
+            if score <= alpha && alpha > Self::MIN {
+                alpha = if delta > Self::ASPIRATION_MAX_WINDOW { Self::MIN } else { (score - delta).max(Self::MIN) };
+            } else if score >= beta && beta < Self::MAX {
+                beta = if delta > Self::ASPIRATION_MAX_WINDOW { Self::MAX } else { (score + delta).min(Self::MAX) };
+            } else {
+                return Some((score, line));
             }
         }
+    }
 
-        if let Some(m) = best_move {
-            println!("Found best move {} {} in {}ms", m.get_from(), m.get_to(), Instant::now().duration_since(start).as_millis());
+    fn search_root(&mut self, board: &BoardState, root_moves: &[Move], depth: usize, alpha: i32, beta: i32) -> Option<(i32, PvLine)> {
+        let mut best = Self::MIN;
+        let mut best_move = None;
+        self.pv_table[0].clear();
+        self.position_keys.clone_from(&self.history);
+        self.position_keys.push(board.zobrist_key());
+        for m in root_moves.iter() {
+            let next_state = board.make_move(*m);
+            self.evaluator.push(board, &next_state);
+            let current = -self.negamax(&next_state, depth - 1, 1, -beta, -alpha.max(best), Some(*m));
+            self.evaluator.pop();
+            if self.stopped() {
+                break;
+            }
+
+            if best_move.is_none() || current > best {
+                best_move = Some(*m);
+                best = current;
+                self.update_pv(0, *m);
+            }
+
+            if best >= beta {
+                break;
+            }
         }
 
-        return best_move.ok_or(GameError::NoPossibleMoveError);
+        return best_move.map(|_| (best, PvLine { score: Self::to_score(best), pv: self.pv_table[0].clone() }));
+    }
+}
+
+impl AiStrategy for Minimax {
+    fn find_optimal_move(&mut self, board: &BoardState, legal_moves: &Vec<Move>) -> Result<Move, GameError> {
+        return self.search(board).map(|r| r.best_move);
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::chess::ai::ai_strategy::{AiStrategy, OpenAi};
-    use crate::chess::BoardState;
+    use crate::chess::ai::ai_strategy::{AiStrategy, Minimax, OpenAi};
+    use crate::chess::ai::evaluate::{Evaluate, MaterialEvaluator};
+    use crate::chess::ai::evaluator::Evaluator;
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::chess::ai::search_limits::SearchLimits;
+    use crate::chess::move_provider::MoveProvider;
+    use crate::chess::ai::search_options::SearchOptions;
+    use crate::chess::ai::search_result::Score;
+    use crate::chess::ai::skill_level::SkillLevel;
+    use crate::chess::{BoardState, Epd};
+
+    const TACTICS: &str = include_str!("../../../resources/tactics.epd");
+
+    fn solved(options: SearchOptions, depth: usize) -> usize {
+        let suite = Epd::parse(TACTICS).unwrap();
+        return suite.iter()
+            .filter(|epd| {
+                let mut minimax = Minimax::with_options(Evaluator::new(), options, depth, 0.0);
+                let m = minimax.find_optimal_move(&epd.board_state, &Vec::new()).unwrap();
+                epd.is_solved_by(m)
+            })
+            .count();
+    }
 
     #[test]
     fn openai_test() {
         // let open_ai = OpenAi::new("");
         // let m = open_ai.find_optimal_move(BoardState::default(), &Vec::new());
     }
+
+    #[test]
+    fn quiescence_improves_tactics() {
+        let suite_size = Epd::parse(TACTICS).unwrap().len();
+        let full_width = solved(SearchOptions::full_width(), 1);
+        let quiescence = solved(SearchOptions::new(), 1);
+        assert!(quiescence > full_width);
+        assert_eq!(suite_size, quiescence);
+    }
+
+    #[test]
+    fn move_ordering_reduces_nodes() {
+        let unordered = SearchOptions { move_ordering: false, transposition_table: false, ..SearchOptions::new() };
+        for fen in ["r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1",
+                    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 0 1"] {
+            let board = BoardState::from_fen(fen).unwrap();
+            let mut nodes = Vec::new();
+            for options in [unordered, SearchOptions::new()] {
+                let mut minimax = Minimax::with_options(Evaluator::new(), options, 3, 0.0);
+                minimax.find_optimal_move(&board, &Vec::new()).unwrap();
+                nodes.push(minimax.processed_states());
+            }
+            assert!(nodes[1] < nodes[0], "{}: ordered {} >= unordered {}", fen, nodes[1], nodes[0]);
+        }
+    }
+
+    #[test]
+    fn selective_search_reduces_nodes() {
+        let mut non_selective = SearchOptions::new();
+        for option in ["null_move_pruning", "late_move_reductions", "futility_pruning",
+                       "reverse_futility_pruning", "check_extensions", "principal_variation_search"] {
+            assert!(non_selective.set(option, false));
+        }
+
+        let board = BoardState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+        let mut nodes = Vec::new();
+        for options in [non_selective, SearchOptions::new()] {
+            let mut minimax = Minimax::with_options(Evaluator::new(), options, 4, 0.0);
+            minimax.find_optimal_move(&board, &Vec::new()).unwrap();
+            nodes.push(minimax.processed_states());
+        }
+        assert!(nodes[1] < nodes[0], "selective {} >= non selective {}", nodes[1], nodes[0]);
+    }
+
+    #[test]
+    fn search_reports_mate_and_principal_variation() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_info_sender(sender);
+        let result = minimax.search(&board).unwrap();
+
+        assert_eq!(Score::Mate(1), result.score);
+        assert_eq!(vec!["Ra8#"], result.pv_san(&board));
+        assert_eq!(3, result.depth);
+        // mate distance pruning cuts lines longer than the mate
+        assert!(result.seldepth >= 1);
+        let depths: Vec<usize> = receiver.try_iter().map(|info| info.depth).collect();
+        assert_eq!(vec![1, 2, 3], depths);
+    }
+
+    #[test]
+    fn search_works_with_any_evaluator() {
+        // Trading rook for queen is better than taking the pawn
+        let board = BoardState::from_fen("3qk3/8/8/p7/1P6/8/8/3RK3 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(MaterialEvaluator::default(), 2, 0.0);
+        assert_eq!("Material", minimax.evaluator().name());
+        assert_eq!(vec!["Rxd8+"], minimax.search(&board).unwrap().pv_san(&board)[..1].to_vec());
+
+        minimax.set_evaluator(Box::new(Evaluator::new()));
+        assert_eq!("Hand-crafted", minimax.evaluator().name());
+        assert_eq!(vec!["Rxd8+"], minimax.search(&board).unwrap().pv_san(&board)[..1].to_vec());
+    }
+
+    #[test]
+    fn multi_pv_returns_distinct_lines() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_multi_pv(3);
+        let result = minimax.search(&board).unwrap();
+
+        assert_eq!(3, result.lines.len());
+        assert_eq!(vec!["Ra8#"], result.lines[0].to_san(&board));
+        assert_eq!(result.pv, result.lines[0].pv);
+        for pair in result.lines.windows(2) {
+            assert_ne!(pair[0].pv[0], pair[1].pv[0]);
+        }
+
+        // more lines than legal moves
+        let board = BoardState::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        minimax.set_multi_pv(10);
+        assert_eq!(3, minimax.search(&board).unwrap().lines.len());
+    }
+
+    #[test]
+    fn single_thread_search_is_deterministic() {
+        let board = BoardState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+        let first = Minimax::new(Evaluator::new(), 4, 0.0).search(&board).unwrap();
+        let second = Minimax::new(Evaluator::new(), 4, 0.0).search(&board).unwrap();
+        assert_eq!(first.pv, second.pv);
+        assert_eq!(first.nodes, second.nodes);
+    }
+
+    #[test]
+    fn lazy_smp_finds_mate_in_one() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 4, 0.0);
+        minimax.set_threads(4);
+        let result = minimax.search(&board).unwrap();
+        assert_eq!("Ra8#", board.to_san(result.best_move));
+        assert_eq!(Score::Mate(1), result.score);
+    }
+
+    #[test]
+    fn search_respects_node_limit() {
+        let board = BoardState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 1, 0.0);
+        let limits = SearchLimits { nodes: Some(2_000), ..SearchLimits::default() };
+        let result = minimax.search_with_limits(&board, &limits).unwrap();
+        assert!(result.nodes < 2_100, "searched {} nodes", result.nodes);
+        assert!(MoveProvider::INSTANCE.legal_moves(&board).contains(&result.best_move));
+    }
+
+    #[test]
+    fn weak_skill_level_limits_search() {
+        let board = BoardState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 6, 0.0);
+        minimax.set_skill_level(SkillLevel::new(2), 7);
+        let result = minimax.search(&board).unwrap();
+        assert!(result.nodes < 2 * SkillLevel::new(2).limits().nodes.unwrap(), "searched {} nodes", result.nodes);
+        assert!(result.depth <= 3);
+        assert_eq!(1, result.lines.len());
+        assert_eq!(result.best_move, result.pv[0]);
+        assert!(MoveProvider::INSTANCE.legal_moves(&board).contains(&result.best_move));
+    }
+
+    #[test]
+    fn contempt_decides_between_repetition_and_losing_line() {
+        let board = BoardState::from_fen("k7/8/8/8/8/5q2/8/K7 w - - 10 40").unwrap();
+        // Kb1 Qf4 Ka1 Qf3 already happened, so Kb1 repeats the position
+        let history = ["k7/8/8/8/8/5q2/8/1K6 b - - 11 40", "k7/8/8/8/5q2/8/8/1K6 w - - 12 41", "k7/8/8/8/5q2/8/8/K7 b - - 13 41"]
+            .iter()
+            .map(|fen| BoardState::from_fen(fen).unwrap().zobrist_key())
+            .collect::<Vec<u64>>();
+
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_history(history.clone());
+        let result = minimax.search(&board).unwrap();
+        assert_eq!("Kb1", board.to_san(result.best_move));
+        assert_eq!(Score::Centipawns(0), result.score);
+
+        // Draw is worse than losing the game on material for very high contempt
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_history(history);
+        minimax.set_contempt(2_000);
+        let result = minimax.search(&board).unwrap();
+        assert_ne!("Kb1", board.to_san(result.best_move));
+    }
+
+    #[test]
+    fn fifty_move_rule_draw_uses_contempt() {
+        let board = BoardState::from_fen("7k/8/8/8/8/8/8/K5Q1 w - - 99 80").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 2, 0.0);
+        minimax.set_contempt(30);
+        assert_eq!(Score::Centipawns(-30), minimax.search(&board).unwrap().score);
+    }
+
+    #[test]
+    fn stop_handle_interrupts_infinite_search() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 1, 0.0);
+        let stop = minimax.stop_handle();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let result = minimax.search_with_limits(&board, &SearchLimits::infinite()).unwrap();
+        stopper.join().unwrap();
+        assert_eq!("Ra8#", board.to_san(result.best_move));
+    }
+
+    #[test]
+    fn stop_before_search_starts_ends_only_that_search() {
+        let board = BoardState::default();
+        let mut minimax = Minimax::new(Evaluator::new(), 1, 0.0);
+        minimax.stop_handle().store(true, Ordering::Relaxed);
+        let result = minimax.search_with_limits(&board, &SearchLimits::infinite()).unwrap();
+        assert_eq!(1, result.depth);
+        assert!(MoveProvider::INSTANCE.legal_moves(&board).contains(&result.best_move));
+
+        assert_eq!(3, minimax.search_with_limits(&board, &SearchLimits::depth(3)).unwrap().depth);
+    }
+
+    #[test]
+    fn search_stops_when_mate_is_found() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 1, 0.0);
+        let limits = SearchLimits { mate: Some(1), ..SearchLimits::default() };
+        let result = minimax.search_with_limits(&board, &limits).unwrap();
+        assert_eq!(Score::Mate(1), result.score);
+        assert!(result.depth < 4);
+    }
+
+    #[test]
+    fn tablebase_scores_are_stored_relative_to_position() {
+        let win = Minimax::TABLEBASE_WIN - 7;
+        assert_eq!(Minimax::TABLEBASE_WIN - 2, Minimax::score_to_tt(win, 5));
+        assert_eq!(Minimax::TABLEBASE_WIN - 3, Minimax::score_from_tt(Minimax::score_to_tt(win, 5), 1));
+        assert_eq!(-Minimax::TABLEBASE_WIN + 3, Minimax::score_from_tt(Minimax::score_to_tt(-win, 5), 1));
+        assert_eq!(1500, Minimax::score_from_tt(Minimax::score_to_tt(1500, 5), 1));
+    }
+
+    #[test]
+    fn mate_scores_survive_aspiration_re_searches() {
+        let mut plain = SearchOptions::new();
+        plain.set("aspiration_windows", false);
+        plain.set("mate_distance_pruning", false);
+
+        // 1. Kb6 Kb8 2. Rh8#
+        let board = BoardState::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let after = board.make_move(MoveProvider::INSTANCE.legal_moves(&board).into_iter()
+            .find(|m| board.to_san(*m) == "Kb6").unwrap());
+        for options in [plain, SearchOptions::new()] {
+            let mut minimax = Minimax::with_options(Evaluator::new(), options, 6, 0.0);
+            assert_eq!(Score::Mate(2), minimax.search(&board).unwrap().score);
+            let result = minimax.search(&after).unwrap();
+            assert_eq!(Score::Mate(-1), result.score);
+            assert_eq!(vec!["Kb8", "Rh8#"], result.pv_san(&after));
+        }
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let board = BoardState::from_fen("6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1").unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 2, 0.0);
+        let m = minimax.find_optimal_move(&board, &Vec::new()).unwrap();
+        assert_eq!("Ra8#", board.to_san(m));
+    }
 }
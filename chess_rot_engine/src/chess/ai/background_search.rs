@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+use crate::chess::{BoardState, GameError};
+use crate::chess::ai::ai_strategy::Minimax;
+use crate::chess::ai::search_limits::SearchLimits;
+use crate::chess::ai::search_result::SearchResult;
+
+/// Search running on its own thread, so the caller stays responsive. Engine is handed back with the
+/// result once the search is joined. Iterations are streamed by the info sender of the engine.
+pub struct BackgroundSearch {
+    board: BoardState,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<(Minimax, Result<SearchResult, GameError>)>,
+}
+
+impl BackgroundSearch {
+    pub fn start(mut minimax: Minimax, board: &BoardState, limits: SearchLimits) -> BackgroundSearch {
+        let searched = *board;
+        let stop = minimax.stop_handle();
+        let handle = thread::spawn(move || {
+            let result = minimax.search_with_limits(&searched, &limits);
+            (minimax, result)
+        });
+        return BackgroundSearch { board: *board, stop, handle };
+    }
+
+    /// Searched position.
+    pub fn board(&self) -> &BoardState {
+        return &self.board;
+    }
+
+    /// Search reached its limits or was stopped, waiting for it does not block.
+    pub fn is_finished(&self) -> bool {
+        return self.handle.is_finished();
+    }
+
+    /// Asks the search to end, it still returns the best move found so far.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits until the search reaches its limits.
+    pub fn wait(self) -> (Minimax, Result<SearchResult, GameError>) {
+        return self.handle.join().expect("search thread panicked");
+    }
+
+    /// Stops the search and waits for its result.
+    pub fn join(self) -> (Minimax, Result<SearchResult, GameError>) {
+        self.stop();
+        return self.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use crate::chess::ai::evaluator::Evaluator;
+
+    #[test]
+    fn test_background_search() {
+        let (sender, receiver) = mpsc::channel();
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_info_sender(sender);
+        let (_, result) = BackgroundSearch::start(minimax, &BoardState::default(), SearchLimits::depth(3)).wait();
+        assert_eq!(3, result.unwrap().depth);
+        assert_eq!(vec![1, 2, 3], receiver.try_iter().map(|info| info.depth).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_join_stops_infinite_search() {
+        let board = BoardState::default();
+        let search = BackgroundSearch::start(Minimax::new(Evaluator::new(), 1, 0.0), &board, SearchLimits::infinite());
+        let (mut minimax, result) = search.join();
+        assert!(result.is_ok());
+        assert_eq!(2, minimax.search_with_limits(&board, &SearchLimits::depth(2)).unwrap().depth);
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, Color, Piece};
+use crate::chess::ai::pawn_structure::distance;
+
+/// Result of a specialized endgame function.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum EndgameScore {
+    // Replaces the general evaluation, from white's perspective
+    Exact(i32),
+    // General evaluation is multiplied by scale / SCALE_NORMAL
+    Scale(i32),
+}
+
+/// Endgames the general evaluation misjudges, recognized by the material on the board.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Endgame {
+    // Lone king against a single minor piece or two knights can not be mated by force
+    InsufficientMaterial,
+    // Bishop and knight mate only in the corner of the bishop's color, stronger side is given
+    BishopKnight(Color),
+    KingPawn(Color),
+    // Only bishops of different colors and pawns are left
+    OppositeBishops,
+}
+
+impl Endgame {
+    pub const SCALE_NORMAL: i32 = 64;
+    // Score of won endgames, on top of it are terms that lead towards the win
+    pub const KNOWN_WIN: i32 = 1000;
+    const LIGHT_SQUARES: BitBoard = BitBoard::from(0x55aa_55aa_55aa_55aa);
+
+    /// Specialized endgame matching the material of the position.
+    pub fn find(board: &BoardState) -> Option<Endgame> {
+        let count = |color: Color, piece: Piece| board.pieces[color.index()][piece.index()].bit_count();
+        let pieces = |color: Color| board.pieces_for_color[color.index()].bit_count() - 1;
+        for strong in [Color::White, Color::Black] {
+            let weak = strong.inverse();
+            if pieces(weak) != 0 {
+                continue;
+            }
+
+            let (knights, bishops, pawns) = (count(strong, Piece::Knight), count(strong, Piece::Bishop), count(strong, Piece::Pawn));
+            let minors_only = pieces(strong) == knights + bishops;
+            if minors_only && (knights + bishops <= 1 || (knights == 2 && bishops == 0)) {
+                return Some(Endgame::InsufficientMaterial);
+            }
+            if minors_only && knights == 1 && bishops == 1 {
+                return Some(Endgame::BishopKnight(strong));
+            }
+            if pawns == 1 && pieces(strong) == 1 {
+                return Some(Endgame::KingPawn(strong));
+            }
+        }
+
+        let bishops_and_pawns = [Color::White, Color::Black].iter()
+            .all(|c| count(*c, Piece::Bishop) == 1 && pieces(*c) == 1 + count(*c, Piece::Pawn));
+        let light_bishops = [Color::White, Color::Black].iter()
+            .filter(|c| !(board.pieces[c.index()][Piece::Bishop.index()] & Self::LIGHT_SQUARES).is_empty())
+            .count();
+        if bishops_and_pawns && light_bishops == 1 {
+            return Some(Endgame::OppositeBishops);
+        }
+        return None;
+    }
+
+    pub fn evaluate(&self, board: &BoardState) -> Option<EndgameScore> {
+        return match *self {
+            Endgame::InsufficientMaterial => Some(EndgameScore::Exact(0)),
+            Endgame::BishopKnight(strong) => Some(Self::bishop_knight(board, strong)),
+            Endgame::KingPawn(strong) => Self::king_pawn(board, strong),
+            Endgame::OppositeBishops => Some(Self::opposite_bishops(board)),
+        };
+    }
+
+    /// Lone king is pushed to a corner of the bishop's color, kings are brought together.
+    fn bishop_knight(board: &BoardState, strong: Color) -> EndgameScore {
+        let light = !(board.pieces[strong.index()][Piece::Bishop.index()] & Self::LIGHT_SQUARES).is_empty();
+        let corners: [usize; 2] = if light { [7, 56] } else { [0, 63] };
+        let weak_king = board.get_king(strong.inverse()).lsb();
+        let corner_distance = corners.iter()
+            .map(|c| (weak_king % 8).abs_diff(c % 8) + (weak_king / 8).abs_diff(c / 8))
+            .min()
+            .unwrap() as i32;
+        let king_distance = distance(board.get_king(strong).lsb(), weak_king);
+
+        let score = Self::KNOWN_WIN + 20 * (14 - corner_distance) + 10 * (7 - king_distance);
+        return EndgameScore::Exact(score * strong.factor());
+    }
+
+    /// Pawn that the lone king can not catch is a win, lone king in front of the pawn or in the corner
+    /// of a rook pawn is a draw. Other positions are left to the general evaluation.
+    fn king_pawn(board: &BoardState, strong: Color) -> Option<EndgameScore> {
+        let pawn = board.pieces[strong.index()][Piece::Pawn.index()].lsb();
+        let (strong_king, weak_king) = (board.get_king(strong).lsb(), board.get_king(strong.inverse()).lsb());
+        let relative_rank = |square: usize| if strong == Color::White { square / 8 } else { 7 - square / 8 };
+        let file = pawn % 8;
+        let promotion = if strong == Color::White { 56 + file } else { file };
+
+        // Square rule, pawn on its starting rank moves two squares at once
+        let pawn_steps = (7 - relative_rank(pawn)).min(5) as i32;
+        let weak_to_move = board.on_move() != strong;
+        let king_steps = distance(weak_king, promotion) - weak_to_move as i32;
+        let path_blocked = strong_king % 8 == file && relative_rank(strong_king) > relative_rank(pawn);
+        if king_steps > pawn_steps && !path_blocked {
+            let score = Self::KNOWN_WIN + Piece::Pawn.value() as i32 + 10 * relative_rank(pawn) as i32;
+            return Some(EndgameScore::Exact(score * strong.factor()));
+        }
+
+        let rook_pawn_corner = (file == 0 || file == 7) && distance(weak_king, promotion) <= 1;
+        let in_front = weak_king % 8 == file && relative_rank(weak_king) > relative_rank(pawn)
+            && relative_rank(strong_king) < relative_rank(pawn);
+        if rook_pawn_corner || in_front {
+            return Some(EndgameScore::Exact(0));
+        }
+        return None;
+    }
+
+    /// Extra pawn is rarely enough to win, the bishops can not fight for the same squares.
+    fn opposite_bishops(board: &BoardState) -> EndgameScore {
+        let pawns = |color: Color| board.pieces[color.index()][Piece::Pawn.index()].bit_count() as i32;
+        let difference = (pawns(Color::White) - pawns(Color::Black)).abs();
+        return EndgameScore::Scale(if difference <= 1 { Self::SCALE_NORMAL / 4 } else { Self::SCALE_NORMAL / 2 });
+    }
+}
+
+impl fmt::Display for Endgame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Endgame::InsufficientMaterial => write!(f, "Insufficient material"),
+            Endgame::BishopKnight(_) => write!(f, "KBNK"),
+            Endgame::KingPawn(_) => write!(f, "KPK"),
+            Endgame::OppositeBishops => write!(f, "Opposite-colored bishops"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endgame(fen: &str) -> Option<Endgame> {
+        return Endgame::find(&BoardState::from_fen(fen).unwrap());
+    }
+
+    fn score(fen: &str) -> Option<EndgameScore> {
+        let board = BoardState::from_fen(fen).unwrap();
+        return Endgame::find(&board).and_then(|e| e.evaluate(&board));
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(Some(Endgame::InsufficientMaterial), endgame("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1"));
+        assert_eq!(Some(Endgame::InsufficientMaterial), endgame("4k1b1/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert_eq!(Some(Endgame::BishopKnight(Color::Black)), endgame("4k1bn/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert_eq!(Some(Endgame::KingPawn(Color::White)), endgame("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert_eq!(Some(Endgame::OppositeBishops), endgame("4k3/5p2/8/3b4/8/4B3/4PP2/4K3 w - - 0 1"));
+        assert_eq!(None, endgame("4k3/5p2/8/3b4/8/3B4/4PP2/4K3 w - - 0 1"));
+        assert_eq!(None, endgame("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"));
+        assert_eq!(None, endgame("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+    }
+
+    #[test]
+    fn test_bishop_knight_drives_to_right_corner() {
+        // Light-squared bishop mates on a8 and h1
+        let right = score("k7/8/2K5/8/8/8/8/4NB2 w - - 0 1");
+        let wrong = score("7k/8/5K2/8/8/8/8/4NB2 w - - 0 1");
+        match (right, wrong) {
+            (Some(EndgameScore::Exact(right)), Some(EndgameScore::Exact(wrong))) => assert!(right > wrong && wrong > Endgame::KNOWN_WIN),
+            _ => panic!("exact scores expected"),
+        }
+    }
+
+    #[test]
+    fn test_king_pawn() {
+        // Black king reaches the square of the pawn only when on move
+        assert!(matches!(score("8/8/8/3k3P/8/8/8/K7 w - - 0 1"), Some(EndgameScore::Exact(s)) if s > Endgame::KNOWN_WIN));
+        assert_eq!(None, score("8/8/8/3k3P/8/8/8/K7 b - - 0 1"));
+        assert!(matches!(score("8/8/8/8/8/3K4/p7/6k1 w - - 0 1"), Some(EndgameScore::Exact(s)) if s < -Endgame::KNOWN_WIN));
+        assert_eq!(Some(EndgameScore::Exact(0)), score("7k/8/8/7P/8/8/8/K7 w - - 0 1"));
+        assert_eq!(Some(EndgameScore::Exact(0)), score("8/8/4k3/8/4P3/8/4K3/8 w - - 0 1"));
+    }
+}
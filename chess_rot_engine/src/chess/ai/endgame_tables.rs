@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, CastlingRight, Color, GameError, Piece, Square};
+use crate::chess::ai::tablebase::{Tablebase, Wdl};
+use crate::chess::move_provider::MoveProvider;
+
+/// Pieces of both sides with kings, e.g. KBNK is white king, bishop and knight against black king.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Material {
+    // Pieces of every color sorted by piece index, king first
+    pieces: [Vec<Piece>; 2],
+}
+
+impl Material {
+    pub const MAX_PIECES: usize = 4;
+
+    pub fn parse(signature: &str) -> Result<Material, GameError> {
+        let invalid = || GameError::ConfigError(format!("invalid material {}, expected e.g. KBNK", signature));
+        let signature = signature.trim().to_ascii_uppercase();
+        let black_king = signature.char_indices().skip(1).find(|(_, c)| *c == 'K').ok_or_else(invalid)?.0;
+        let mut pieces: [Vec<Piece>; 2] = [Vec::new(), Vec::new()];
+        for (color, side) in [signature[..black_king].chars(), signature[black_king..].chars()].into_iter().enumerate() {
+            for (i, c) in side.enumerate() {
+                let piece = match c {
+                    'K' if i == 0 => Piece::King,
+                    _ if i == 0 => return Err(invalid()),
+                    'Q' => Piece::Queen,
+                    'R' => Piece::Rook,
+                    'B' => Piece::Bishop,
+                    'N' => Piece::Knight,
+                    'P' => Piece::Pawn,
+                    _ => return Err(invalid()),
+                };
+                pieces[color].push(piece);
+            }
+            pieces[color].sort_by_key(|p| p.index());
+        }
+
+        let material = Material { pieces };
+        if material.count() > Self::MAX_PIECES {
+            return Err(GameError::ConfigError(format!("{} has more than {} pieces", material, Self::MAX_PIECES)));
+        }
+        return Ok(material);
+    }
+
+    pub fn of(board: &BoardState) -> Material {
+        let mut pieces: [Vec<Piece>; 2] = [Vec::new(), Vec::new()];
+        for color in [Color::White, Color::Black] {
+            for index in 0..6 {
+                let piece = Piece::try_from(index).unwrap();
+                for _ in 0..board.pieces[color.index()][index].bit_count() {
+                    pieces[color.index()].push(piece);
+                }
+            }
+        }
+        return Material { pieces };
+    }
+
+    pub fn count(&self) -> usize {
+        return self.pieces[0].len() + self.pieces[1].len();
+    }
+
+    pub fn flipped(&self) -> Material {
+        return Material { pieces: [self.pieces[1].clone(), self.pieces[0].clone()] };
+    }
+
+    /// Orientation tables are generated in, the stronger side is white.
+    pub fn canonical(&self) -> Material {
+        let strength = |pieces: &Vec<Piece>| (pieces.iter().map(|p| p.value()).sum::<u32>(), pieces.len());
+        return if strength(&self.pieces[1]) > strength(&self.pieces[0]) { self.flipped() } else { self.clone() };
+    }
+
+    /// Materials the position can turn into by a capture or a promotion.
+    fn conversions(&self) -> Vec<Material> {
+        let mut conversions = Vec::new();
+        for color in 0..2 {
+            for (i, piece) in self.pieces[color].iter().enumerate().skip(1) {
+                let mut captured = self.clone();
+                captured.pieces[color].remove(i);
+                conversions.push(captured);
+
+                if *piece == Piece::Pawn {
+                    for promotion in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                        let mut promoted = self.clone();
+                        promoted.pieces[color][i] = promotion;
+                        promoted.pieces[color].sort_by_key(|p| p.index());
+                        conversions.push(promoted);
+                    }
+                }
+            }
+        }
+        return conversions;
+    }
+
+    /// En passant is not part of table positions, it changes results once both sides have pawns.
+    fn check_supported(&self) -> Result<(), GameError> {
+        if self.pieces.iter().all(|pieces| pieces.contains(&Piece::Pawn)) {
+            return Err(GameError::ConfigError(format!("{} has pawns of both sides, en passant is not supported", self)));
+        }
+        return Ok(());
+    }
+
+    // Pieces in the order their squares are part of the table index
+    fn slots(&self) -> Vec<(Color, Piece)> {
+        let white = self.pieces[0].iter().map(|p| (Color::White, *p));
+        return white.chain(self.pieces[1].iter().map(|p| (Color::Black, *p))).collect();
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let signature: String = self.pieces.iter().flatten().map(|p| p.to_char().to_ascii_uppercase()).collect();
+        return write!(f, "{}", signature);
+    }
+}
+
+/// Distance to mate of every position with given material, indexed by squares of the pieces
+/// and the side on move. Castling and en passant are not part of the positions.
+pub struct EndgameTable {
+    material: Material,
+    slots: Vec<(Color, Piece)>,
+    // 0 is draw, win in n plies is n and loss in n plies is -n - 1, so being mated is -1
+    values: Vec<i8>,
+}
+
+impl EndgameTable {
+    const MAGIC: &'static [u8; 4] = b"CRTB";
+    const VERSION: u8 = 1;
+    const MAX_PLIES: usize = 126;
+
+    fn new(material: &Material) -> Self {
+        let slots = material.slots();
+        let size = 2 * 64usize.pow(slots.len() as u32);
+        return Self { material: material.clone(), slots, values: vec![0; size] };
+    }
+
+    pub fn material(&self) -> &Material {
+        return &self.material;
+    }
+
+    /// Plies of the longest forced mate in the table.
+    pub fn longest_mate(&self) -> u32 {
+        return self.values.iter().copied().max().unwrap_or(0).max(0) as u32;
+    }
+
+    /// Retrograde analysis, starts from mates and walks back by unmoves. A position is won once
+    /// a move reaches a lost one and lost once every move reaches a won one. Positions after
+    /// captures and promotions are looked up in the tables of the smaller material.
+    /// Reference: https://www.chessprogramming.org/Retrograde_Analysis
+    pub fn generate(material: &Material, tables: &EndgameTables) -> Result<EndgameTable, GameError> {
+        material.check_supported()?;
+        let mut table = Self::new(material);
+        let size = table.values.len();
+        let mut valid = vec![false; size];
+        let mut resolved = vec![false; size];
+        // Moves without capture or promotion whose result is not known yet
+        let mut open_moves = vec![0u8; size];
+        // Capture or promotion keeps at least a draw, so the position can not be lost
+        let mut holds = vec![false; size];
+        // Longest loss reachable by a capture or promotion
+        let mut conversion_loss = vec![0u8; size];
+        // Positions to resolve for every distance, even ones are lost and odd ones won for the side on move
+        let mut plies: Vec<Vec<usize>> = vec![Vec::new(); u8::MAX as usize + 2];
+
+        for index in 0..size {
+            let board = match table.board(index) {
+                Some(board) if !MoveProvider::INSTANCE.is_in_check(&board.make_null_move()) => board,
+                _ => continue,
+            };
+            valid[index] = true;
+
+            let moves = MoveProvider::INSTANCE.legal_moves(&board);
+            if moves.is_empty() {
+                // Stalemate stays unresolved, so it counts as draw for positions leading to it
+                if MoveProvider::INSTANCE.is_in_check(&board) {
+                    plies[0].push(index);
+                }
+                continue;
+            }
+
+            let mut fastest_win: Option<u32> = None;
+            for m in moves {
+                if !board.is_capture(m) && !board.is_promotion(m) {
+                    open_moves[index] += 1;
+                    continue;
+                }
+
+                let child = board.make_move(m);
+                let missing = || GameError::ConfigError(format!("{} needs table of {}", material, Material::of(&child)));
+                match tables.probe(&child).ok_or_else(missing)? {
+                    (Wdl::Loss, distance) => fastest_win = Some(fastest_win.map_or(distance + 1, |w| w.min(distance + 1))),
+                    (Wdl::Draw, _) => holds[index] = true,
+                    (Wdl::Win, distance) => conversion_loss[index] = conversion_loss[index].max(distance as u8 + 1),
+                }
+            }
+
+            if let Some(win) = fastest_win {
+                holds[index] = true;
+                plies[win as usize].push(index);
+            } else if open_moves[index] == 0 && !holds[index] {
+                plies[conversion_loss[index] as usize].push(index);
+            }
+        }
+
+        for distance in 0..plies.len() {
+            for index in std::mem::take(&mut plies[distance]) {
+                if resolved[index] {
+                    continue;
+                }
+                if distance > Self::MAX_PLIES {
+                    return Err(GameError::ConfigError(format!("{} has mates longer than {} plies", material, Self::MAX_PLIES)));
+                }
+                resolved[index] = true;
+                table.values[index] = if distance % 2 == 1 { distance as i8 } else { -(distance as i8) - 1 };
+
+                for previous in table.unmoves(index) {
+                    if !valid[previous] || resolved[previous] {
+                        continue;
+                    }
+                    if distance % 2 == 0 {
+                        plies[distance + 1].push(previous);
+                    } else {
+                        open_moves[previous] -= 1;
+                        if open_moves[previous] == 0 && !holds[previous] {
+                            plies[(distance + 1).max(conversion_loss[previous] as usize)].push(previous);
+                        }
+                    }
+                }
+            }
+        }
+        return Ok(table);
+    }
+
+    /// Result and distance to mate in plies from the perspective of the side on move.
+    pub fn probe(&self, board: &BoardState) -> Option<(Wdl, u32)> {
+        if Material::of(board) != self.material {
+            return None;
+        }
+
+        let mut remaining = board.pieces;
+        let mut index = 0;
+        for (color, piece) in self.slots.iter() {
+            let pieces = &mut remaining[color.index()][piece.index()];
+            let square = pieces.lsb();
+            *pieces = pieces.remove_bit(square as u64);
+            index = index * 64 + square;
+        }
+        let value = self.values[index * 2 + board.on_move().index()];
+        return Some(match value {
+            0 => (Wdl::Draw, 0),
+            v if v > 0 => (Wdl::Win, v as u32),
+            v => (Wdl::Loss, (-(v as i32) - 1) as u32),
+        });
+    }
+
+    /// Position of the index, None when pieces share a square or pawns stand on the first or last rank.
+    fn board(&self, index: usize) -> Option<BoardState> {
+        let squares = self.squares(index);
+        let mut pieces = [[BitBoard::empty(); 6]; 2];
+        let mut occupancy = BitBoard::empty();
+        for ((color, piece), square) in self.slots.iter().zip(squares.iter()) {
+            let bit = BitBoard::from(1 << square);
+            if !(occupancy & bit).is_empty() || (*piece == Piece::Pawn && (square / 8 == 0 || square / 8 == 7)) {
+                return None;
+            }
+            occupancy |= bit;
+            pieces[color.index()][piece.index()] |= bit;
+        }
+
+        let on_move = if index % 2 == 0 { Color::White } else { Color::Black };
+        return Some(BoardState::from_parts(pieces, on_move, CastlingRight::from_raw(CastlingRight::NO_CASTLING), None, 0, 1));
+    }
+
+    fn squares(&self, index: usize) -> Vec<usize> {
+        let mut squares = vec![0; self.slots.len()];
+        let mut rest = index / 2;
+        for square in squares.iter_mut().rev() {
+            *square = rest % 64;
+            rest /= 64;
+        }
+        return squares;
+    }
+
+    fn index_of(squares: &[usize], on_move: Color) -> usize {
+        return squares.iter().fold(0, |index, square| index * 64 + square) * 2 + on_move.index();
+    }
+
+    /// Indexes of positions the index could be reached from by a move without capture or promotion.
+    fn unmoves(&self, index: usize) -> Vec<usize> {
+        let mut squares = self.squares(index);
+        let mover = if index % 2 == 0 { Color::Black } else { Color::White };
+        let occupancy = squares.iter().fold(BitBoard::empty(), |bb, s| bb | BitBoard::from(1 << s));
+        let mut unmoves = Vec::new();
+        for (slot, (color, piece)) in self.slots.iter().enumerate() {
+            if *color != mover {
+                continue;
+            }
+
+            let square = squares[slot];
+            let mut origins = match piece {
+                Piece::Pawn => Self::pawn_origins(mover, square, occupancy),
+                _ => MoveProvider::INSTANCE.piece_attacks(*piece, mover, Square::from_usize(square), occupancy) & !occupancy,
+            };
+            while !origins.is_empty() {
+                let origin = origins.lsb();
+                origins = origins.remove_bit(origin as u64);
+                squares[slot] = origin;
+                unmoves.push(Self::index_of(&squares, mover));
+            }
+            squares[slot] = square;
+        }
+        return unmoves;
+    }
+
+    fn pawn_origins(color: Color, square: usize, occupancy: BitBoard) -> BitBoard {
+        let (back, start_rank, double_rank) = match color {
+            Color::White => (-8, 1, 3),
+            Color::Black => (8, 6, 4),
+        };
+        let single = (square as isize + back) as usize;
+        if occupancy.is_bit_set(single as u64) || single / 8 == 0 || single / 8 == 7 {
+            return BitBoard::empty();
+        }
+
+        let mut origins = BitBoard::from(1 << single);
+        let double = (single as isize + back) as usize;
+        if square / 8 == double_rank && double / 8 == start_rank && !occupancy.is_bit_set(double as u64) {
+            origins |= BitBoard::from(1 << double);
+        }
+        return origins;
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let signature = self.material.to_string();
+        let mut bytes = Self::MAGIC.to_vec();
+        bytes.push(Self::VERSION);
+        bytes.push(signature.len() as u8);
+        bytes.extend_from_slice(signature.as_bytes());
+        bytes.extend(self.values.iter().map(|v| *v as u8));
+        return bytes;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<EndgameTable, GameError> {
+        let invalid = |reason: &str| GameError::ConfigError(format!("invalid endgame table: {}", reason));
+        if bytes.len() < 6 || &bytes[0..4] != Self::MAGIC {
+            return Err(invalid("missing header"));
+        }
+        if bytes[4] != Self::VERSION {
+            return Err(invalid("unsupported version"));
+        }
+
+        let end = 6 + bytes[5] as usize;
+        let signature = bytes.get(6..end).and_then(|s| std::str::from_utf8(s).ok()).ok_or_else(|| invalid("bad material"))?;
+        let mut table = Self::new(&Material::parse(signature)?);
+        if bytes.len() - end != table.values.len() {
+            return Err(invalid("wrong size"));
+        }
+        table.values = bytes[end..].iter().map(|v| *v as i8).collect();
+        return Ok(table);
+    }
+}
+
+impl fmt::Debug for EndgameTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "EndgameTable({}, {} positions)", self.material, self.values.len());
+    }
+}
+
+/// Endgame tables generated by the engine, probed in both color orientations.
+#[derive(Debug, Default)]
+pub struct EndgameTables {
+    tables: HashMap<Material, EndgameTable>,
+}
+
+impl EndgameTables {
+    pub const FILE_EXTENSION: &'static str = "crtb";
+
+    pub fn new() -> Self {
+        return Self { tables: HashMap::new() };
+    }
+
+    pub fn materials(&self) -> Vec<&Material> {
+        return self.tables.keys().collect();
+    }
+
+    pub fn table(&self, material: &Material) -> Option<&EndgameTable> {
+        return self.tables.get(material);
+    }
+
+    pub fn insert(&mut self, table: EndgameTable) {
+        self.tables.insert(table.material.clone(), table);
+    }
+
+    /// Generates the table together with tables of every material it converts into.
+    /// Returns materials of the tables that were generated.
+    pub fn generate(&mut self, material: &Material) -> Result<Vec<Material>, GameError> {
+        let material = material.canonical();
+        material.check_supported()?;
+        if material.count() <= 2 || self.tables.contains_key(&material) || self.tables.contains_key(&material.flipped()) {
+            return Ok(Vec::new());
+        }
+
+        let mut generated = Vec::new();
+        for conversion in material.conversions() {
+            generated.extend(self.generate(&conversion)?);
+        }
+        let table = EndgameTable::generate(&material, self)?;
+        self.insert(table);
+        generated.push(material);
+        return Ok(generated);
+    }
+
+    /// Result and distance to mate in plies from the perspective of the side on move.
+    pub fn probe(&self, board: &BoardState) -> Option<(Wdl, u32)> {
+        // Tables have no castling rights, only the flags of done castling may be set
+        if board.castling.raw() & 0x0f != CastlingRight::NO_CASTLING {
+            return None;
+        }
+
+        let material = Material::of(board);
+        if material.count() == 2 {
+            return Some((Wdl::Draw, 0));
+        }
+        if let Some(table) = self.tables.get(&material) {
+            return table.probe(board);
+        }
+        return self.tables.get(&material.flipped())?.probe(&Self::flipped(board));
+    }
+
+    /// Same position with colors swapped and the board mirrored.
+    fn flipped(board: &BoardState) -> BoardState {
+        let mut pieces = [[BitBoard::empty(); 6]; 2];
+        for color in [Color::White, Color::Black] {
+            for (i, bitboard) in board.pieces[color.index()].iter().enumerate() {
+                pieces[color.inverse().index()][i] = bitboard.mirrored_vertically();
+            }
+        }
+        let en_passant = board.en_passant_position.map(|s| Square::from_usize(s.as_usize() ^ 56));
+        return BoardState::from_parts(pieces, board.on_move().inverse(), CastlingRight::from_raw(CastlingRight::NO_CASTLING),
+                                      en_passant, board.half_move_clock(), board.full_moves());
+    }
+
+    /// Loads every table file of the directory.
+    pub fn load_dir(dir: &Path) -> Result<Self, GameError> {
+        let error = |e: std::io::Error| GameError::ConfigError(format!("cannot read {}: {}", dir.display(), e));
+        let mut tables = Self::new();
+        for entry in fs::read_dir(dir).map_err(error)? {
+            let path = entry.map_err(error)?.path();
+            if path.extension().is_some_and(|e| e == Self::FILE_EXTENSION) {
+                tables.insert(EndgameTable::from_bytes(&fs::read(&path).map_err(error)?)?);
+            }
+        }
+        return Ok(tables);
+    }
+
+    /// Writes every table to the directory as <material>.crtb.
+    pub fn save_dir(&self, dir: &Path) -> Result<(), GameError> {
+        let error = |e: std::io::Error| GameError::ConfigError(format!("cannot write {}: {}", dir.display(), e));
+        fs::create_dir_all(dir).map_err(error)?;
+        for table in self.tables.values() {
+            let path = dir.join(format!("{}.{}", table.material, Self::FILE_EXTENSION));
+            fs::write(path, table.to_bytes()).map_err(error)?;
+        }
+        return Ok(());
+    }
+}
+
+impl Tablebase for EndgameTables {
+    fn max_pieces(&self) -> u32 {
+        return self.tables.keys().map(|m| m.count() as u32).max().unwrap_or(2);
+    }
+
+    fn probe_wdl(&self, board: &BoardState) -> Option<Wdl> {
+        return self.probe(board).map(|(wdl, _)| wdl);
+    }
+
+    fn probe_dtz(&self, board: &BoardState) -> Option<i32> {
+        return self.probe(board).map(|(wdl, plies)| match wdl {
+            Wdl::Win => plies as i32,
+            Wdl::Draw => 0,
+            Wdl::Loss => -(plies as i32),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(tables: &EndgameTables, fen: &str) -> Option<(Wdl, u32)> {
+        return tables.probe(&BoardState::from_fen(fen).unwrap());
+    }
+
+    fn longest_mate(tables: &EndgameTables, signature: &str) -> u32 {
+        return tables.table(&Material::parse(signature).unwrap()).unwrap().longest_mate();
+    }
+
+    #[test]
+    fn test_material() {
+        assert_eq!("KBNK", Material::parse("knbk").unwrap().to_string());
+        assert_eq!("KQK", Material::parse("KKQ").unwrap().canonical().to_string());
+        assert_eq!("KQKR", Material::of(&BoardState::from_fen("4k3/8/8/8/8/8/8/r2QK3 w - - 0 1").unwrap()).to_string());
+        assert!(Material::parse("QKK").is_err());
+        assert!(Material::parse("KQ").is_err());
+        assert!(Material::parse("KQRBK").is_err());
+    }
+
+    #[test]
+    fn test_rook_and_queen_endings() {
+        let mut tables = EndgameTables::new();
+        let generated = tables.generate(&Material::parse("KQK").unwrap()).unwrap();
+        assert_eq!(vec!["KQK".to_string()], generated.iter().map(|m| m.to_string()).collect::<Vec<_>>());
+        tables.generate(&Material::parse("KKR").unwrap()).unwrap();
+
+        // Longest mates are known, 10 moves with queen and 16 with rook
+        assert_eq!(19, longest_mate(&tables, "KQK"));
+        assert_eq!(31, longest_mate(&tables, "KRK"));
+
+        assert_eq!(Some((Wdl::Win, 1)), probe(&tables, "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"));
+        assert_eq!(Some((Wdl::Loss, 0)), probe(&tables, "Q6k/8/6K1/8/8/8/8/8 b - - 0 1"));
+        assert_eq!(Some((Wdl::Loss, 0)), probe(&tables, "q6K/8/6k1/8/8/8/8/8 w - - 0 1"));
+        // Rook is taken at once
+        assert_eq!(Some((Wdl::Draw, 0)), probe(&tables, "8/8/8/8/8/2k5/8/rK6 w - - 0 1"));
+        assert_eq!(None, probe(&tables, "8/8/8/8/8/2k5/8/bK6 w - - 0 1"));
+
+        let table = &tables.tables[&Material::parse("KQK").unwrap()];
+        let loaded = EndgameTable::from_bytes(&table.to_bytes()).unwrap();
+        assert_eq!(table.values, loaded.values);
+        assert!(EndgameTable::from_bytes(&table.to_bytes()[..100]).is_err());
+    }
+
+    #[test]
+    fn test_pawn_endings() {
+        let mut tables = EndgameTables::new();
+        tables.generate(&Material::parse("KPK").unwrap()).unwrap();
+        // King on the sixth rank in front of its pawn wins whoever is on move
+        assert!(matches!(probe(&tables, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"), Some((Wdl::Win, _))));
+        assert!(matches!(probe(&tables, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"), Some((Wdl::Loss, _))));
+        assert!(matches!(probe(&tables, "8/8/8/8/4p3/4k3/8/4K3 w - - 0 1"), Some((Wdl::Loss, _))));
+        // Defending king in front of the pawn holds, the pawn push only leads to stalemate
+        assert_eq!(Some((Wdl::Draw, 0)), probe(&tables, "4k3/8/4P3/4K3/8/8/8/8 w - - 0 1"));
+        assert_eq!(Some((Wdl::Draw, 0)), probe(&tables, "7k/8/8/8/8/8/7P/7K w - - 0 1"));
+
+        // Distance after promotion continues in the queen table
+        let (wdl, plies) = probe(&tables, "8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+        let (_, after_promotion) = probe(&tables, "4Q3/8/8/8/8/8/k7/4K3 b - - 0 1").unwrap();
+        assert_eq!((Wdl::Win, after_promotion + 1), (wdl, plies));
+        assert_eq!(55, longest_mate(&tables, "KPK"));
+
+        assert!(tables.generate(&Material::parse("KPKP").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_bishop_and_knight_ending() {
+        let mut tables = EndgameTables::new();
+        tables.generate(&Material::parse("KBNK").unwrap()).unwrap();
+        // Longest mate is known, 33 moves
+        assert_eq!(65, longest_mate(&tables, "KBNK"));
+        assert_eq!(Some((Wdl::Draw, 0)), probe(&tables, "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+    }
+}
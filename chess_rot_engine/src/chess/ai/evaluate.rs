@@ -0,0 +1,118 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use crate::chess::{BoardState, Color, GameError, Piece};
+use crate::chess::ai::evaluation_parameters::EvaluationParameters;
+use crate::chess::ai::evaluator::Evaluator;
+use crate::chess::ai::nnue::{Network, NnueEvaluator};
+use crate::chess::ai::personality::Personality;
+
+/// Static evaluation used by the search, implementations can be swapped without touching the search.
+/// Every search thread works with its own copy, so implementations may keep caches without locking.
+pub trait Evaluate: Debug + Send {
+    /// Evaluation of the position from white's perspective in centipawns.
+    fn evaluate(&self, board: &BoardState) -> i32;
+
+    /// Search moves from the parent to the child position, evaluators keeping incremental state update it.
+    fn push(&mut self, _parent: &BoardState, _child: &BoardState) {}
+
+    /// Search returns to the parent of the last pushed position.
+    fn pop(&mut self) {}
+
+    fn name(&self) -> &'static str;
+
+    fn box_clone(&self) -> Box<dyn Evaluate>;
+}
+
+impl Clone for Box<dyn Evaluate> {
+    fn clone(&self) -> Self {
+        return self.box_clone();
+    }
+}
+
+/// Counts only material, useful for testing the search.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct MaterialEvaluator {}
+
+impl Evaluate for MaterialEvaluator {
+    fn evaluate(&self, board: &BoardState) -> i32 {
+        let mut score = 0;
+        for color in [Color::White, Color::Black] {
+            for p in Piece::LIST.iter().filter(|p| **p != Piece::King) {
+                let count = board.pieces[color.index()][p.index()].bit_count() as i32;
+                score += count * p.value() as i32 * color.factor();
+            }
+        }
+        return score;
+    }
+
+    fn name(&self) -> &'static str {
+        return "Material";
+    }
+
+    fn box_clone(&self) -> Box<dyn Evaluate> {
+        return Box::new(*self);
+    }
+}
+
+/// Evaluators selectable in the UI.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EvaluatorKind {
+    HandCrafted,
+    Material,
+    Neural,
+}
+
+impl EvaluatorKind {
+    pub const LIST: [EvaluatorKind; 3] = [EvaluatorKind::HandCrafted, EvaluatorKind::Material, EvaluatorKind::Neural];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            EvaluatorKind::HandCrafted => "Hand-crafted",
+            EvaluatorKind::Material => "Material",
+            EvaluatorKind::Neural => "Neural",
+        };
+    }
+
+    /// New evaluator of this kind, weights of the personality replace weights of the parameters.
+    /// Neural evaluator needs a loaded network.
+    pub fn create(self, personality: &Personality, params: &EvaluationParameters,
+                  network: Option<&Arc<Network>>) -> Result<Box<dyn Evaluate>, GameError> {
+        return match self {
+            EvaluatorKind::HandCrafted => {
+                let params = EvaluationParameters { weights: personality.weights, ..params.clone() };
+                Ok(Box::new(Evaluator::with_parameters(params)))
+            }
+            EvaluatorKind::Material => Ok(Box::new(MaterialEvaluator::default())),
+            EvaluatorKind::Neural => match network {
+                Some(network) => Ok(Box::new(NnueEvaluator::new(network.clone()))),
+                None => Err(GameError::ConfigError("no network loaded".to_string())),
+            },
+        };
+    }
+}
+
+impl Default for EvaluatorKind {
+    fn default() -> Self {
+        return EvaluatorKind::HandCrafted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_material_evaluator() {
+        let evaluator = MaterialEvaluator::default();
+        assert_eq!(0, evaluator.evaluate(&BoardState::default()));
+        let board = BoardState::from_fen("4k3/8/8/8/8/8/P7/RN2K3 w - - 0 1").unwrap();
+        assert_eq!(925, evaluator.evaluate(&board));
+
+        let personality = Personality::default();
+        let params = EvaluationParameters::DEFAULT;
+        let boxed = EvaluatorKind::Material.create(&personality, &params, None).unwrap();
+        assert_eq!(925, boxed.clone().evaluate(&board));
+        assert_eq!("Material", boxed.name());
+        assert!(EvaluatorKind::Neural.create(&personality, &params, None).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+use crate::chess::ai::endgame_evaluation::{Endgame, EndgameScore};
+use crate::chess::ai::piece_square_tables as pst;
+
+/// Middle game and end game value of a single evaluation term.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TermScore {
+    pub middle_game: i32,
+    pub end_game: i32,
+}
+
+impl TermScore {
+    pub const fn new(middle_game: i32, end_game: i32) -> Self {
+        return Self { middle_game, end_game };
+    }
+
+    /// Blends middle game and end game value by the game phase.
+    pub fn tapered(&self, phase: i32) -> i32 {
+        return (self.middle_game * phase + self.end_game * (pst::MAX_PHASE - phase)) / pst::MAX_PHASE;
+    }
+}
+
+impl Add for TermScore {
+    type Output = TermScore;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        return TermScore::new(self.middle_game + rhs.middle_game, self.end_game + rhs.end_game);
+    }
+}
+
+impl Sub for TermScore {
+    type Output = TermScore;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        return TermScore::new(self.middle_game - rhs.middle_game, self.end_game - rhs.end_game);
+    }
+}
+
+/// Evaluation terms of one color from the perspective of that color.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SideBreakdown {
+    pub material: TermScore,
+    pub piece_square: TermScore,
+    pub pawns: TermScore,
+    pub passed_pawns: TermScore,
+    pub mobility: TermScore,
+    pub king_safety: TermScore,
+    // Bishop pair, castling and pieces close to the enemy king
+    pub other: TermScore,
+}
+
+impl SideBreakdown {
+    pub fn terms(&self) -> [(&'static str, TermScore); 7] {
+        return [
+            ("Material", self.material),
+            ("Piece-square", self.piece_square),
+            ("Pawns", self.pawns),
+            ("Passed pawns", self.passed_pawns),
+            ("Mobility", self.mobility),
+            ("King safety", self.king_safety),
+            ("Other", self.other),
+        ];
+    }
+
+    pub fn sum(&self) -> TermScore {
+        return self.terms().iter().fold(TermScore::default(), |sum, (_, term)| sum + *term);
+    }
+}
+
+/// Evaluation split into terms for both colors, returned by `Evaluator::explain`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EvaluationBreakdown {
+    // Indexed by color
+    pub sides: [SideBreakdown; 2],
+    pub phase: i32,
+    // Applied after tapering, from white's perspective
+    pub risk: i32,
+    // Specialized evaluation recognized by the material, it overrides or scales the terms
+    pub endgame: Option<(Endgame, EndgameScore)>,
+}
+
+impl EvaluationBreakdown {
+    /// Evaluation from white's perspective, same as `Evaluate::evaluate` returns.
+    pub fn total(&self) -> i32 {
+        let general = (self.sides[0].sum() - self.sides[1].sum()).tapered(self.phase);
+        return match self.endgame {
+            Some((_, EndgameScore::Exact(score))) => score,
+            Some((_, EndgameScore::Scale(scale))) => general * scale / Endgame::SCALE_NORMAL + self.risk,
+            None => general + self.risk,
+        };
+    }
+}
+
+impl fmt::Display for EvaluationBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<14}|{:^15}|{:^15}|{:^15}", "Term", "White", "Black", "Total")?;
+        writeln!(f, "{:<14}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7} ", "", "MG", "EG", "MG", "EG", "MG", "EG")?;
+        writeln!(f, "{}", "-".repeat(62))?;
+        let white = self.sides[0].terms();
+        let black = self.sides[1].terms();
+        for ((name, w), (_, b)) in white.iter().zip(black.iter()) {
+            let total = *w - *b;
+            writeln!(f, "{:<14}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7} ", name, w.middle_game, w.end_game,
+                     b.middle_game, b.end_game, total.middle_game, total.end_game)?;
+        }
+        writeln!(f, "{}", "-".repeat(62))?;
+        writeln!(f, "Phase: {}/{}", self.phase, pst::MAX_PHASE)?;
+        writeln!(f, "Risk: {}", self.risk)?;
+        match self.endgame {
+            Some((endgame, EndgameScore::Exact(score))) => writeln!(f, "Endgame: {} (exact {})", endgame, score)?,
+            Some((endgame, EndgameScore::Scale(scale))) => writeln!(f, "Endgame: {} (scale {}/{})", endgame, scale, Endgame::SCALE_NORMAL)?,
+            None => {}
+        }
+        write!(f, "Total: {} (white's perspective)", self.total())
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::chess::ai::evaluator::EvaluationWeights;
+use crate::chess::ai::king_safety::KingSafetyParameters;
+use crate::chess::ai::mobility::MobilityParameters;
+use crate::chess::ai::pawn_structure::PawnParameters;
+use crate::chess::ai::piece_square_tables::PieceSquareTables;
+use crate::chess::GameError;
+
+/// Every number used by the hand-crafted evaluation. Defaults are compiled in, files in TOML
+/// or JSON format may override any subset of them, missing values keep their defaults.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvaluationParameters {
+    pub weights: EvaluationWeights,
+    pub piece_square: PieceSquareTables,
+    pub pawns: PawnParameters,
+    pub mobility: MobilityParameters,
+    pub king_safety: KingSafetyParameters,
+}
+
+impl EvaluationParameters {
+    pub const DEFAULT: Self = Self {
+        weights: EvaluationWeights::new(),
+        piece_square: PieceSquareTables::DEFAULT,
+        pawns: PawnParameters::DEFAULT,
+        mobility: MobilityParameters::DEFAULT,
+        king_safety: KingSafetyParameters::DEFAULT,
+    };
+
+    pub fn from_toml(str: &str) -> Result<Self, GameError> {
+        return toml::from_str(str).map_err(|e| GameError::ConfigError(e.to_string()));
+    }
+
+    pub fn from_json(str: &str) -> Result<Self, GameError> {
+        return serde_json::from_str(str).map_err(|e| GameError::ConfigError(e.to_string()));
+    }
+
+    pub fn to_toml(&self) -> String {
+        return toml::to_string(self).expect("parameters are always valid TOML");
+    }
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).expect("parameters are always valid JSON");
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        return load_config(path);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        return save_config(self, path);
+    }
+
+    /// Values changed by tuning in a fixed order. Weights are left out, they are percentages of the other values.
+    pub fn values_mut(&mut self) -> Vec<&mut i32> {
+        let mut values: Vec<&mut i32> = Vec::new();
+        let tables = &mut self.piece_square;
+        values.extend(tables.middle_game_values.iter_mut().chain(tables.end_game_values.iter_mut()));
+        values.extend(tables.middle_game.iter_mut().chain(tables.end_game.iter_mut()).flatten());
+
+        let pawns = &mut self.pawns;
+        values.extend([&mut pawns.doubled.0, &mut pawns.doubled.1, &mut pawns.backward.0, &mut pawns.backward.1]);
+        values.extend(pawns.connected.iter_mut().chain(pawns.passed_middle_game.iter_mut()));
+        values.extend(pawns.passed_end_game.iter_mut().chain(pawns.passed_free_path.iter_mut()));
+        values.extend([&mut pawns.enemy_king_distance, &mut pawns.own_king_distance]);
+
+        let mobility = &mut self.mobility;
+        values.extend(mobility.average_squares.iter_mut().chain(mobility.middle_game.iter_mut()));
+        values.extend(mobility.end_game.iter_mut().chain(mobility.attack_units.iter_mut()));
+
+        let king_safety = &mut self.king_safety;
+        values.extend(king_safety.shield.iter_mut().chain(king_safety.storm.iter_mut()));
+        values.extend([&mut king_safety.missing_shield, &mut king_safety.semi_open_file, &mut king_safety.open_file,
+            &mut king_safety.min_attackers, &mut king_safety.danger_per_unit, &mut king_safety.max_danger]);
+        return values;
+    }
+
+    pub fn values(&self) -> Vec<i32> {
+        return self.clone().values_mut().into_iter().map(|v| *v).collect();
+    }
+
+    pub fn set_values(&mut self, values: &[i32]) {
+        for (value, new) in self.values_mut().into_iter().zip(values) {
+            *value = *new;
+        }
+    }
+}
+
+/// Loads value from a file, files ending with .json are JSON, anything else is TOML.
+pub(crate) fn load_config<T: DeserializeOwned>(path: &Path) -> Result<T, GameError> {
+    let str = fs::read_to_string(path)
+        .map_err(|e| GameError::ConfigError(format!("cannot read {}: {}", path.display(), e)))?;
+    return match is_json(path) {
+        true => serde_json::from_str(&str).map_err(|e| GameError::ConfigError(e.to_string())),
+        false => toml::from_str(&str).map_err(|e| GameError::ConfigError(e.to_string())),
+    };
+}
+
+pub(crate) fn save_config<T: Serialize>(value: &T, path: &Path) -> Result<(), GameError> {
+    let str = match is_json(path) {
+        true => serde_json::to_string_pretty(value).map_err(|e| GameError::ConfigError(e.to_string()))?,
+        false => toml::to_string(value).map_err(|e| GameError::ConfigError(e.to_string()))?,
+    };
+    return fs::write(path, str)
+        .map_err(|e| GameError::ConfigError(format!("cannot write {}: {}", path.display(), e)));
+}
+
+fn is_json(path: &Path) -> bool {
+    return path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+}
+
+impl Default for EvaluationParameters {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut params = EvaluationParameters::default();
+        params.weights.castling = 55;
+        params.piece_square.middle_game[5][12] = 7;
+        params.king_safety.storm = [0, -1, -2, -3];
+        assert_eq!(params, EvaluationParameters::from_toml(&params.to_toml()).unwrap());
+        assert_eq!(params, EvaluationParameters::from_json(&params.to_json()).unwrap());
+    }
+
+    #[test]
+    fn test_partial_files() {
+        let params = EvaluationParameters::from_toml("[pawns]\ndoubled = [-20, -40]\n").unwrap();
+        assert_eq!((-20, -40), params.pawns.doubled);
+        assert_eq!(PawnParameters::DEFAULT.backward, params.pawns.backward);
+        assert_eq!(PieceSquareTables::DEFAULT, params.piece_square);
+
+        let params = EvaluationParameters::from_json(r#"{"weights": {"mobility": 50}}"#).unwrap();
+        assert_eq!(50, params.weights.mobility);
+        assert_eq!(EvaluationWeights::new().castling, params.weights.castling);
+
+        let params = EvaluationParameters::from_toml("[piece_square.end_game.pawn]\nrank_7 = [1, 2, 3, 4, 5, 6, 7, 8]\n").unwrap();
+        assert_eq!([1, 2, 3, 4, 5, 6, 7, 8], params.piece_square.end_game[5][8..16]);
+        assert_eq!(PieceSquareTables::DEFAULT.end_game[5][16..], params.piece_square.end_game[5][16..]);
+        assert_eq!(PieceSquareTables::DEFAULT.end_game[4], params.piece_square.end_game[4]);
+        assert_eq!(PieceSquareTables::DEFAULT.middle_game, params.piece_square.middle_game);
+
+        assert!(EvaluationParameters::from_toml("[pawns]\ndoubled = 3\n").is_err());
+        assert!(EvaluationParameters::from_toml("[piece_square.end_game.pawn]\nrank_9 = [0, 0, 0, 0, 0, 0, 0, 0]\n").is_err());
+        assert!(EvaluationParameters::from_json(r#"{"piece_square": {"end_game": {"pawn": {"rank_2": [1, 2]}}}}"#).is_err());
+    }
+}
@@ -1,60 +1,371 @@
-use crate::bitboard::BitBoard;
-use crate::chess::{BoardState, Color, Move, Piece};
+use std::cell::RefCell;
+use serde::{Deserialize, Serialize};
+use crate::chess::{BoardState, Color, Piece};
+use crate::chess::ai::evaluate::Evaluate;
+use crate::chess::ai::endgame_evaluation::{Endgame, EndgameScore};
+use crate::chess::ai::evaluation_breakdown::{EvaluationBreakdown, SideBreakdown, TermScore};
+use crate::chess::ai::evaluation_parameters::EvaluationParameters;
+use crate::chess::ai::king_safety::KingSafety;
+use crate::chess::ai::mobility::Mobility;
+use crate::chess::ai::pawn_structure::{distance, PawnHashTable, PawnStructure};
+use crate::chess::ai::piece_square_tables as pst;
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Evaluator {}
+/// Weights of the evaluation terms, changing them changes playing style of the engine.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvaluationWeights {
+    // Percentage of the standard piece values
+    pub material: i32,
+    pub castling: i32,
+    pub isolated_pawn: i32,
+    pub double_bishop: i32,
+    // Percentages of mobility and king safety terms
+    pub mobility: i32,
+    pub king_safety: i32,
+    // Bonus for every piece close to the enemy king
+    pub king_attack: i32,
+    // Bonus for every piece left on the board given to the side that is ahead in material,
+    // negative values make the engine simplify when it is winning
+    pub risk: i32,
+}
+
+impl EvaluationWeights {
+    pub const fn new() -> Self {
+        return Self {
+            material: 100,
+            castling: 30,
+            isolated_pawn: -15,
+            double_bishop: 45,
+            mobility: 100,
+            king_safety: 100,
+            king_attack: 0,
+            risk: 0,
+        };
+    }
+}
+
+impl Default for EvaluationWeights {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Tapered evaluation, every term has middle game and end game value which are blended
+/// by the amount of material left on the board.
+/// Reference: https://www.chessprogramming.org/Tapered_Eval
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    params: EvaluationParameters,
+    pawn_table: RefCell<PawnHashTable>,
+}
 
 impl Evaluator {
-    const CASTLING_VALUE: i32 = 65;
-    const CHECK_VALUE: i32 = 40;
-    const CHECK_MATE_VALUE: i32 = 10000;
-    const ISOLATED_PAWN_VALUE: i32 = -15;
-    const DOUBLE_BISHOP_VALUE: i32 = 45;
+    // Chebyshev distance from the enemy king counted as attack
+    const KING_ATTACK_DISTANCE: i32 = 2;
 
     pub fn new() -> Self {
-        return Self {};
+        return Self::with_weights(EvaluationWeights::new());
     }
 
-    pub fn evaluate(&self, board: &BoardState, legal_moves: &Vec<Move>, depth: usize) -> i32 {
-        return Self::calculate_score(Color::White, board, legal_moves, depth)
-            - Self::calculate_score(Color::Black, board, legal_moves, depth);
+    pub fn with_weights(weights: EvaluationWeights) -> Self {
+        return Self::with_parameters(EvaluationParameters { weights, ..EvaluationParameters::DEFAULT });
     }
 
-    fn calculate_score(color: Color, board_state: &BoardState, legal_moves: &Vec<Move>, depth: usize) -> i32 {
-        let mut score = 0;
-        let pieces = board_state.pieces[color.index()];
-        for p in Piece::LIST {
-            let piece_bb = pieces[p.index()];
-            let piece_count = piece_bb.bit_count();
-            score += (piece_count * p.value()) as i32;
+    pub fn with_parameters(params: EvaluationParameters) -> Self {
+        return Self::with_pawn_table(params, PawnHashTable::DEFAULT_ENTRIES);
+    }
+
+    /// Evaluator with the pawn hash table of given number of entries, small tables suit short lived evaluators.
+    pub fn with_pawn_table(params: EvaluationParameters, entries: usize) -> Self {
+        return Self { params, pawn_table: RefCell::new(PawnHashTable::new(entries)) };
+    }
+
+    pub fn weights(&self) -> &EvaluationWeights {
+        return &self.params.weights;
+    }
+
+    pub fn parameters(&self) -> &EvaluationParameters {
+        return &self.params;
+    }
 
-            if p == Piece::Bishop {
-                score += Self::DOUBLE_BISHOP_VALUE;
+    /// Game phase from 24 with all pieces on the board to 0 with only pawns and kings.
+    pub fn phase(board: &BoardState) -> i32 {
+        let mut phase = 0;
+        for color in [Color::White, Color::Black] {
+            for p in Piece::LIST {
+                phase += board.pieces[color.index()][p.index()].bit_count() as i32 * pst::PHASE_VALUES[p.index()];
             }
+        }
+        // Promotions can bring more material than the starting position
+        return phase.min(pst::MAX_PHASE);
+    }
 
-            if board_state.castling.castled(color) {
-                score += Self::CASTLING_VALUE;
+    /// Evaluation split into terms for both colors, its total is the evaluation.
+    pub fn explain(&self, board: &BoardState) -> EvaluationBreakdown {
+        let pawns = self.pawn_structure(board);
+        let mobility = self.mobility(board);
+        let mut sides = [SideBreakdown::default(); 2];
+        for color in [Color::White, Color::Black] {
+            let side = &mut sides[color.index()];
+            (side.material, side.piece_square, side.other) = self.piece_scores(color, board);
+            (side.pawns, side.passed_pawns) = self.pawn_scores(color, board, &pawns);
+            side.mobility = self.mobility_score(&mobility[color.index()]);
+            side.king_safety = self.king_safety_score(color, board, &mobility[color.inverse().index()]);
+        }
+        let endgame = Endgame::find(board).and_then(|e| e.evaluate(board).map(|score| (e, score)));
+        return EvaluationBreakdown { sides, phase: Self::phase(board), risk: self.risk_score(board), endgame };
+    }
+
+    /// Material, piece-square tables and bonuses of pieces of the color.
+    fn piece_scores(&self, color: Color, board: &BoardState) -> (TermScore, TermScore, TermScore) {
+        let mut material = TermScore::default();
+        let mut piece_square = TermScore::default();
+        let mut other = TermScore::default();
+        let pieces = board.pieces[color.index()];
+        let tables = &self.params.piece_square;
+        for p in Piece::LIST {
+            let mut iter = pieces[p.index()];
+            while !iter.is_empty() {
+                let square = iter.lsb();
+                iter = iter.remove_bit(square as u64);
+                material.middle_game += tables.middle_game_values[p.index()] * self.params.weights.material / 100;
+                material.end_game += tables.end_game_values[p.index()] * self.params.weights.material / 100;
+                piece_square.middle_game += tables.middle_game(p, color, square);
+                piece_square.end_game += tables.end_game(p, color, square);
             }
+        }
+
+        if pieces[Piece::Bishop.index()].bit_count() >= 2 {
+            other.middle_game += self.params.weights.double_bishop;
+            other.end_game += self.params.weights.double_bishop;
+        }
+
+        if board.castling.castled(color) {
+            other.middle_game += self.params.weights.castling;
+        }
+
+        if self.params.weights.king_attack != 0 {
+            other.middle_game += self.params.weights.king_attack * Self::pieces_near_enemy_king(color, board);
+        }
+        return (material, piece_square, other);
+    }
+
+    /// Pawn structure and passed pawns of the color.
+    fn pawn_scores(&self, color: Color, board: &BoardState, pawns: &PawnStructure) -> (TermScore, TermScore) {
+        let structure = TermScore::new(pawns.middle_game[color.index()], pawns.end_game[color.index()]);
+        let (passed_mg, passed_eg) = pawns.passed_pawns(color, board, &self.params.pawns);
+        return (structure, TermScore::new(passed_mg, passed_eg));
+    }
+
+    fn mobility(&self, board: &BoardState) -> [Mobility; 2] {
+        return [
+            Mobility::evaluate(Color::White, board, &self.params.mobility),
+            Mobility::evaluate(Color::Black, board, &self.params.mobility),
+        ];
+    }
+
+    fn mobility_score(&self, mobility: &Mobility) -> TermScore {
+        return TermScore::new(mobility.middle_game * self.params.weights.mobility / 100,
+                              mobility.end_game * self.params.weights.mobility / 100);
+    }
+
+    fn king_safety_score(&self, color: Color, board: &BoardState, enemy: &Mobility) -> TermScore {
+        let king_safety = KingSafety::evaluate(color, board, enemy, &self.params.king_safety);
+        return TermScore::new(king_safety * self.params.weights.king_safety / 100, 0);
+    }
+
+    fn pawn_structure(&self, board: &BoardState) -> PawnStructure {
+        let key = board.pawn_key();
+        if let Some(structure) = self.pawn_table.borrow().probe(key) {
+            return structure;
+        }
+
+        let structure = PawnStructure::evaluate(board, &self.params.pawns, self.params.weights.isolated_pawn);
+        self.pawn_table.borrow_mut().store(key, structure);
+        return structure;
+    }
 
-            let pawns = pieces[Piece::Pawn.index()];
-            let mut iter = pawns.clone();
-            let direction = color.factor() * (-1);
-            loop {
-                if iter.is_empty() { break; }
-                let pawn_position = iter.lsb() as i32;
-                iter = BitBoard::from(iter.raw() & (iter.raw() - 1));
-                if !pawns.is_bit_set((pawn_position + direction * 9) as u64) {
-                    score += Self::ISOLATED_PAWN_VALUE;
+    fn pieces_near_enemy_king(color: Color, board_state: &BoardState) -> i32 {
+        let king = board_state.pieces[color.inverse().index()][Piece::King.index()];
+        if king.is_empty() {
+            return 0;
+        }
+
+        let king = king.lsb();
+        let mut count = 0;
+        for p in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            let mut iter = board_state.pieces[color.index()][p.index()];
+            while !iter.is_empty() {
+                let square = iter.lsb();
+                iter = iter.remove_bit(square as u64);
+                if distance(square, king) <= Self::KING_ATTACK_DISTANCE {
+                    count += 1;
                 }
+            }
+        }
+        return count;
+    }
 
-                if !pawns.is_bit_set((pawn_position + direction * 7) as u64) {
-                    score += Self::ISOLATED_PAWN_VALUE;
+    /// Rewards keeping pieces on the board for the side that is ahead, from white's perspective.
+    fn risk_score(&self, board_state: &BoardState) -> i32 {
+        if self.params.weights.risk == 0 {
+            return 0;
+        }
+
+        let mut material = 0;
+        let mut pieces = 0;
+        for color in [Color::White, Color::Black] {
+            for p in [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+                let count = board_state.pieces[color.index()][p.index()].bit_count();
+                material += (count * p.value()) as i32 * color.factor();
+                if p != Piece::Pawn {
+                    pieces += count as i32;
                 }
             }
+        }
+        return material.signum() * self.params.weights.risk * pieces;
+    }
+}
+
+impl Evaluate for Evaluator {
+    fn evaluate(&self, board: &BoardState) -> i32 {
+        let endgame = Endgame::find(board).and_then(|e| e.evaluate(board));
+        if let Some(EndgameScore::Exact(score)) = endgame {
+            return score;
+        }
+
+        let pawns = self.pawn_structure(board);
+        let mobility = self.mobility(board);
+        let mut score = TermScore::default();
+        for color in [Color::White, Color::Black] {
+            let (material, piece_square, other) = self.piece_scores(color, board);
+            let (structure, passed) = self.pawn_scores(color, board, &pawns);
+            let side = material + piece_square + other + structure + passed
+                + self.mobility_score(&mobility[color.index()])
+                + self.king_safety_score(color, board, &mobility[color.inverse().index()]);
+            score = if color == Color::White { score + side } else { score - side };
+        }
+        let general = score.tapered(Self::phase(board));
+        return match endgame {
+            Some(EndgameScore::Scale(scale)) => general * scale / Endgame::SCALE_NORMAL + self.risk_score(board),
+            _ => general + self.risk_score(board),
+        };
+    }
+
+    fn name(&self) -> &'static str {
+        return "Hand-crafted";
+    }
+
+    fn box_clone(&self) -> Box<dyn Evaluate> {
+        return Box::new(self.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::CastlingRight;
+    use crate::chess::ai::personality::Personality;
+
+    /// Position with colors swapped and board flipped upside down.
+    fn mirrored(board: &BoardState) -> BoardState {
+        let mut mirrored = board.clone();
+        for color in [Color::White, Color::Black] {
+            let opposite = color.inverse().index();
+            mirrored.pieces_for_color[color.index()] = board.pieces_for_color[opposite].mirrored_vertically();
+            for p in Piece::LIST {
+                mirrored.pieces[color.index()][p.index()] = board.pieces[opposite][p.index()].mirrored_vertically();
+            }
+        }
+        mirrored.color_on_move = board.color_on_move.inverse();
+        mirrored.castling = CastlingRight::from_raw(CastlingRight::NO_CASTLING);
+        for color in [Color::White, Color::Black] {
+            if board.castling.castled(color.inverse()) {
+                mirrored.castling = mirrored.castling.set_castled(color);
+            }
+        }
+        return mirrored;
+    }
 
-            score += legal_moves.len() as i32;
+    #[test]
+    fn test_symmetric_evaluation() {
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP1B1PPP/R2QKB1R b KQ - 3 8",
+            "8/2k5/3p4/p2P1p2/P2P1P2/8/6K1/8 w - - 0 1",
+            "6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1",
+            "k7/8/2K5/8/8/8/8/4NB2 w - - 0 1",
+            "4k3/5p2/8/3b4/8/4B3/4PP2/4K3 w - - 0 1",
+        ];
+        let evaluators = [Evaluator::new(), Personality::aggressive().evaluator(), Personality::defensive().evaluator()];
+        for fen in positions {
+            let board = BoardState::from_fen(fen).unwrap();
+            for evaluator in evaluators.iter() {
+                assert_eq!(evaluator.evaluate(&board), -evaluator.evaluate(&mirrored(&board)), "{}", fen);
+            }
         }
+        assert_eq!(0, Evaluator::new().evaluate(&BoardState::default()));
+    }
+
+    #[test]
+    fn test_phase_and_bishop_pair() {
+        assert_eq!(24, Evaluator::phase(&BoardState::default()));
+        assert_eq!(0, Evaluator::phase(&BoardState::from_fen("8/2k5/3p4/8/8/8/6K1/8 w - - 0 1").unwrap()));
+
+        let pair = BoardState::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        let single = BoardState::from_fen("4k3/8/8/8/8/8/8/2B1K3 b - - 0 1").unwrap();
+        let evaluator = Evaluator::new();
+        let without_pair = Evaluator::with_weights(EvaluationWeights { double_bishop: 0, ..EvaluationWeights::new() });
+        assert_eq!(45, evaluator.evaluate(&pair) - without_pair.evaluate(&pair));
+        assert_eq!(0, evaluator.evaluate(&single) - without_pair.evaluate(&single));
+    }
+
+    #[test]
+    fn test_explain() {
+        let evaluator = Personality::aggressive().evaluator();
+        let board = BoardState::from_fen("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP1B1PPP/R2QKB1R b KQ - 3 8").unwrap();
+        let breakdown = evaluator.explain(&board);
+        assert_eq!(evaluator.evaluate(&board), breakdown.total());
+        // white is a pawn up
+        assert_eq!(TermScore::new(82, 94), breakdown.sides[0].material - breakdown.sides[1].material);
+
+        let table = breakdown.to_string();
+        assert!(table.contains("King safety"));
+        assert!(table.ends_with(&format!("Total: {} (white's perspective)", breakdown.total())));
+    }
+
+    #[test]
+    fn test_explain_matches_evaluate() {
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2k5/3p4/p2P1p2/P2P1P2/8/6K1/8 w - - 0 1",
+            "6k1/5ppp/8/8/7r/8/8/R3B1K1 w - - 0 1",
+            "2kr3r/ppp2ppp/2n5/8/1q6/2N2Q2/PPP2PPP/2KR3R b - - 4 14",
+            "4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1",
+            "8/8/8/3k4/8/8/8/2BNK3 w - - 0 1",
+            "4k3/5p2/8/3b4/8/4B3/4PP2/4K3 w - - 0 1",
+        ];
+        let evaluators = [Evaluator::new(), Personality::aggressive().evaluator(), Personality::defensive().evaluator()];
+        for fen in positions {
+            let board = BoardState::from_fen(fen).unwrap();
+            for evaluator in evaluators.iter() {
+                assert_eq!(evaluator.explain(&board).total(), evaluator.evaluate(&board), "{}", fen);
+            }
+        }
+    }
+
+    #[test]
+    fn test_endgames() {
+        let evaluator = Evaluator::new();
+        let knights = BoardState::from_fen("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1").unwrap();
+        assert_eq!(0, evaluator.evaluate(&knights));
 
-        return score;
+        // Extra pawn with opposite-colored bishops is worth much less than with bishops of the same color
+        let opposite = BoardState::from_fen("4k3/5p2/8/3b4/8/4B3/4PP2/4K3 w - - 0 1").unwrap();
+        let same = BoardState::from_fen("4k3/5p2/8/3b4/8/3B4/4PP2/4K3 w - - 0 1").unwrap();
+        assert!(evaluator.evaluate(&opposite) < evaluator.evaluate(&same) / 2);
+        assert!(evaluator.explain(&opposite).to_string().contains("Endgame: Opposite-colored bishops"));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, Color, Piece};
+use crate::chess::ai::mobility::Mobility;
+
+/// Values of the king safety terms, all of them apply only in the middle game.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KingSafetyParameters {
+    // Indexed by rank distance of the nearest own pawn in front of the king
+    pub shield: [i32; 3],
+    pub missing_shield: i32,
+    // Indexed by rank distance of the nearest enemy pawn in front of the king
+    pub storm: [i32; 4],
+    pub semi_open_file: i32,
+    pub open_file: i32,
+    // Single attacker can hardly mate, danger grows with square of attack units
+    pub min_attackers: i32,
+    pub danger_per_unit: i32,
+    pub max_danger: i32,
+}
+
+impl KingSafetyParameters {
+    pub const DEFAULT: Self = Self {
+        shield: [0, 15, 8],
+        missing_shield: -15,
+        storm: [0, -5, -20, -10],
+        semi_open_file: -15,
+        open_file: -25,
+        min_attackers: 2,
+        danger_per_unit: 2,
+        max_danger: 500,
+    };
+}
+
+impl Default for KingSafetyParameters {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+/// Middle game safety of the king, pawns in front of it, open files next to it and enemy attacks on its zone.
+/// Reference: https://www.chessprogramming.org/King_Safety
+pub struct KingSafety {}
+
+impl KingSafety {
+    pub fn evaluate(color: Color, board: &BoardState, enemy: &Mobility, params: &KingSafetyParameters) -> i32 {
+        let king = board.get_king(color);
+        if king.is_empty() {
+            return 0;
+        }
+
+        let king = king.lsb();
+        let (king_file, king_rank) = (king % 8, king / 8);
+        let own = board.pieces[color.index()][Piece::Pawn.index()];
+        let enemy_pawns = board.pieces[color.inverse().index()][Piece::Pawn.index()];
+        let relative_rank = match color {
+            Color::White => king_rank,
+            Color::Black => 7 - king_rank,
+        };
+        let front = match color {
+            Color::White => BitBoard::ranks_above(king_rank),
+            Color::Black => BitBoard::ranks_below(king_rank),
+        };
+
+        let mut score = 0;
+        for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+            let file_bb = BitBoard::file(file);
+            if relative_rank <= 1 {
+                score += match Self::nearest_distance(color, own & file_bb & front, king_rank) {
+                    Some(distance) if distance < params.shield.len() => params.shield[distance],
+                    _ => params.missing_shield,
+                };
+            }
+
+            if let Some(distance) = Self::nearest_distance(color, enemy_pawns & file_bb & front, king_rank) {
+                if distance < params.storm.len() {
+                    score += params.storm[distance];
+                }
+            }
+
+            if (own & file_bb).is_empty() {
+                score += if (enemy_pawns & file_bb).is_empty() { params.open_file } else { params.semi_open_file };
+            }
+        }
+
+        if enemy.king_attackers >= params.min_attackers {
+            score -= (enemy.king_attack_units * enemy.king_attack_units * params.danger_per_unit).min(params.max_danger);
+        }
+        return score;
+    }
+
+    /// Rank distance from the king to the closest of the pawns in front of it.
+    fn nearest_distance(color: Color, pawns: BitBoard, king_rank: usize) -> Option<usize> {
+        if pawns.is_empty() {
+            return None;
+        }
+
+        let nearest = match color {
+            Color::White => pawns.lsb(),
+            Color::Black => pawns.msb(),
+        };
+        return Some((nearest / 8).abs_diff(king_rank));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::ai::mobility::MobilityParameters;
+
+    fn king_safety(fen: &str, color: Color) -> i32 {
+        let board = BoardState::from_fen(fen).unwrap();
+        let enemy = Mobility::evaluate(color.inverse(), &board, &MobilityParameters::DEFAULT);
+        return KingSafety::evaluate(color, &board, &enemy, &KingSafetyParameters::DEFAULT);
+    }
+
+    #[test]
+    fn test_pawn_shield_and_open_files() {
+        let sheltered = king_safety("6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1", Color::White);
+        let advanced = king_safety("6k1/8/8/8/8/5PPP/8/6K1 w - - 0 1", Color::White);
+        let open = king_safety("6k1/8/8/8/8/8/5P1P/6K1 w - - 0 1", Color::White);
+        assert!(sheltered > advanced);
+        assert!(advanced > open);
+        assert_eq!(sheltered, king_safety("6k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", Color::Black));
+    }
+
+    #[test]
+    fn test_king_zone_attacks() {
+        let quiet = king_safety("6k1/5ppp/8/8/8/8/1Q6/1R4K1 b - - 0 1", Color::Black);
+        let attacked = king_safety("6k1/5ppp/8/6N1/8/8/7Q/6K1 b - - 0 1", Color::Black);
+        assert!(quiet > attacked);
+    }
+}
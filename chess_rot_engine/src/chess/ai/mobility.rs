@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, Color, Piece, Square};
+use crate::chess::move_provider::MoveProvider;
+
+/// Activity of pieces of one color, safe squares they reach and their attacks on the enemy king zone.
+/// Reference: https://www.chessprogramming.org/Mobility
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mobility {
+    pub middle_game: i32,
+    pub end_game: i32,
+    // Weighted attacks on squares around the enemy king and number of pieces making them
+    pub king_attack_units: i32,
+    pub king_attackers: i32,
+}
+
+/// Tables indexed by knight, bishop, rook and queen.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MobilityParameters {
+    pub average_squares: [i32; 4],
+    // Bonus per square above or below the average number of squares
+    pub middle_game: [i32; 4],
+    pub end_game: [i32; 4],
+    // Units per attacked square of the enemy king zone
+    pub attack_units: [i32; 4],
+}
+
+impl MobilityParameters {
+    pub const DEFAULT: Self = Self {
+        average_squares: [4, 7, 7, 14],
+        middle_game: [4, 5, 2, 1],
+        end_game: [4, 5, 4, 2],
+        attack_units: [2, 2, 3, 5],
+    };
+}
+
+impl Default for MobilityParameters {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+impl Mobility {
+    const PIECES: [Piece; 4] = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen];
+
+    pub fn evaluate(color: Color, board: &BoardState, params: &MobilityParameters) -> Self {
+        let own = board.pieces_for_color[color.index()];
+        let occupancy = board.all_pieces();
+        // Squares attacked by enemy pawns are not worth counting
+        let safe = !(own | MoveProvider::INSTANCE.pawn_attacks(board, color.inverse()));
+        let king_zone = Self::king_zone(board, color.inverse());
+
+        let mut mobility = Self { middle_game: 0, end_game: 0, king_attack_units: 0, king_attackers: 0 };
+        for (i, piece) in Self::PIECES.iter().enumerate() {
+            let mut iter = board.pieces[color.index()][piece.index()];
+            while !iter.is_empty() {
+                let square = iter.lsb();
+                iter = iter.remove_bit(square as u64);
+                let attacks = MoveProvider::INSTANCE.piece_attacks(*piece, color, Square::from_usize(square), occupancy);
+                let squares = (attacks & safe).bit_count() as i32 - params.average_squares[i];
+                mobility.middle_game += squares * params.middle_game[i];
+                mobility.end_game += squares * params.end_game[i];
+
+                let zone_attacks = (attacks & king_zone).bit_count() as i32;
+                if zone_attacks > 0 {
+                    mobility.king_attack_units += zone_attacks * params.attack_units[i];
+                    mobility.king_attackers += 1;
+                }
+            }
+        }
+        return mobility;
+    }
+
+    /// King square with squares around it.
+    pub fn king_zone(board: &BoardState, color: Color) -> BitBoard {
+        let king = board.get_king(color);
+        if king.is_empty() {
+            return BitBoard::empty();
+        }
+        let square = Square::from_usize(king.lsb());
+        return king | MoveProvider::INSTANCE.piece_attacks(Piece::King, color, square, board.all_pieces());
+    }
+}
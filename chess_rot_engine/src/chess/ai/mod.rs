@@ -1,5 +1,28 @@
 pub mod ai_strategy;
+pub mod background_search;
 mod ai_move_provider;
+pub mod endgame_evaluation;
+pub mod endgame_tables;
+pub mod evaluate;
+pub mod evaluation_breakdown;
+pub mod evaluation_parameters;
 pub mod evaluator;
+pub mod king_safety;
+pub mod mobility;
+pub mod search_limits;
+pub mod search_options;
+pub mod search_result;
+pub mod skill_level;
+pub mod tablebase;
+pub mod training_data;
+pub mod move_ordering;
+pub mod nnue;
+pub mod opening_book;
+pub mod pawn_structure;
+pub mod personality;
+pub mod piece_square_tables;
+pub mod ponder;
+pub mod transposition_table;
+pub mod tuning;
 
 pub use ai_move_provider::*;
\ No newline at end of file
@@ -0,0 +1,222 @@
+use crate::chess::{BoardState, Color, Move, Piece, Square};
+use crate::chess::move_provider::MoveProvider;
+
+/// Orders moves so that the ones most likely to cause a cutoff are searched first.
+/// Reference: https://www.chessprogramming.org/Move_Ordering
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveOrdering {
+    killers: Vec<[Option<Move>; 2]>,
+    // Butterfly history indexed by color, from and to square
+    history: Vec<[[i32; 64]; 64]>,
+    // Quiet move that refuted previous move, indexed by from and to square of previous move
+    countermoves: Vec<[Option<Move>; 64]>,
+}
+
+impl MoveOrdering {
+    const HASH_MOVE_SCORE: i32 = 10_000_000;
+    const GOOD_CAPTURE_SCORE: i32 = 1_000_000;
+    const FIRST_KILLER_SCORE: i32 = 900_000;
+    const SECOND_KILLER_SCORE: i32 = 800_000;
+    const COUNTERMOVE_SCORE: i32 = 700_000;
+    const BAD_CAPTURE_SCORE: i32 = -1_000_000;
+    const MAX_HISTORY: i32 = 16_384;
+
+    // Piece values used for exchanges, indexed by piece index
+    const SEE_VALUES: [i32; 7] = [20000, 900, 500, 330, 325, 100, 0];
+    // Victim and attacker ranks used by MVV-LVA, indexed by piece index
+    const MVV_LVA_RANKS: [i32; 7] = [6, 5, 4, 3, 2, 1, 0];
+
+    pub fn new(max_ply: usize) -> Self {
+        return Self {
+            killers: vec![[None; 2]; max_ply],
+            history: vec![[[0; 64]; 64]; 2],
+            countermoves: vec![[None; 64]; 64],
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.killers.fill([None; 2]);
+        self.history.fill([[0; 64]; 64]);
+        self.countermoves.fill([None; 64]);
+    }
+
+    /// Sorts moves by hash move, good captures (MVV-LVA), killers, countermove, history and finally losing captures.
+    pub fn order_moves(&self, board: &BoardState, moves: Vec<Move>, hash_move: Option<Move>, ply: usize, previous: Option<Move>) -> Vec<Move> {
+        let mut scored: Vec<(i32, Move)> = moves.into_iter()
+            .map(|m| (self.score(board, m, hash_move, ply, previous), m))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        return scored.into_iter().map(|(_, m)| m).collect();
+    }
+
+    /// Sorts captures by most valuable victim and least valuable attacker.
+    pub fn order_captures(board: &BoardState, moves: Vec<Move>) -> Vec<Move> {
+        let mut scored: Vec<(i32, Move)> = moves.into_iter()
+            .map(|m| (Self::mvv_lva(board, m), m))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        return scored.into_iter().map(|(_, m)| m).collect();
+    }
+
+    fn score(&self, board: &BoardState, m: Move, hash_move: Option<Move>, ply: usize, previous: Option<Move>) -> i32 {
+        if hash_move == Some(m) {
+            return Self::HASH_MOVE_SCORE;
+        }
+
+        if board.is_capture(m) || board.is_promotion(m) {
+            let mvv_lva = Self::mvv_lva(board, m);
+            return if Self::static_exchange(board, m) >= 0 {
+                Self::GOOD_CAPTURE_SCORE + mvv_lva
+            } else {
+                Self::BAD_CAPTURE_SCORE + mvv_lva
+            };
+        }
+
+        if let Some(killers) = self.killers.get(ply) {
+            if killers[0] == Some(m) {
+                return Self::FIRST_KILLER_SCORE;
+            } else if killers[1] == Some(m) {
+                return Self::SECOND_KILLER_SCORE;
+            }
+        }
+
+        if let Some(p) = previous {
+            if self.countermoves[p.get_from().as_usize()][p.get_to().as_usize()] == Some(m) {
+                return Self::COUNTERMOVE_SCORE;
+            }
+        }
+
+        return self.history[board.color_on_move.index()][m.get_from().as_usize()][m.get_to().as_usize()];
+    }
+
+    fn mvv_lva(board: &BoardState, m: Move) -> i32 {
+        let victim = board.captured_piece(m).unwrap_or(Piece::None);
+        let attacker = board.get_piece_at(m.get_from().raw()).map_or(Piece::None, |(p, _)| p);
+        let promotion = if board.is_promotion(m) { Self::MVV_LVA_RANKS[m.get_target_piece().index()] } else { 0 };
+        return (Self::MVV_LVA_RANKS[victim.index()] + promotion) * 8 - Self::MVV_LVA_RANKS[attacker.index()];
+    }
+
+    /// Rewards quiet move that caused a beta cutoff and penalizes quiet moves searched before it.
+    pub fn update_quiet(&mut self, color: Color, m: Move, searched_quiets: &[Move], ply: usize, depth: usize, previous: Option<Move>) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(m) {
+                killers[1] = killers[0];
+                killers[0] = Some(m);
+            }
+        }
+
+        if let Some(p) = previous {
+            self.countermoves[p.get_from().as_usize()][p.get_to().as_usize()] = Some(m);
+        }
+
+        let bonus = (depth * depth).min(400) as i32;
+        self.update_history(color, m, bonus);
+        for quiet in searched_quiets.iter().filter(|q| **q != m) {
+            self.update_history(color, *quiet, -bonus);
+        }
+    }
+
+    fn update_history(&mut self, color: Color, m: Move, bonus: i32) {
+        // gravity keeps values bounded and lets old statistics fade out
+        let entry = &mut self.history[color.index()][m.get_from().as_usize()][m.get_to().as_usize()];
+        *entry += bonus - *entry * bonus.abs() / Self::MAX_HISTORY;
+    }
+
+    /// Material balance of the exchange started by the move on its target square.
+    /// Reference: https://www.chessprogramming.org/SEE_-_The_Swap_Algorithm
+    pub fn static_exchange(board: &BoardState, m: Move) -> i32 {
+        let to = m.get_to();
+        let attacker = match board.get_piece_at(m.get_from().raw()) {
+            Some((p, _)) => p,
+            None => return 0,
+        };
+
+        let mut gain = [0i32; 32];
+        let mut depth = 0;
+        let mut side = board.color_on_move;
+        let mut occupancy = board.all_pieces();
+        let mut from_bb = m.get_from().as_bb();
+
+        gain[0] = Self::SEE_VALUES[board.captured_piece(m).unwrap_or(Piece::None).index()];
+        let mut attacker_value = Self::SEE_VALUES[attacker.index()];
+        if board.is_promotion(m) {
+            let promoted = Self::SEE_VALUES[m.get_target_piece().index()];
+            gain[0] += promoted - Self::SEE_VALUES[Piece::Pawn.index()];
+            attacker_value = promoted;
+        }
+
+        loop {
+            depth += 1;
+            side = side.inverse();
+            gain[depth] = attacker_value - gain[depth - 1];
+            if (-gain[depth - 1]).max(gain[depth]) < 0 || depth == gain.len() - 1 {
+                break;
+            }
+
+            occupancy ^= from_bb;
+            let attackers = MoveProvider::INSTANCE.attackers_to(board, to, occupancy) & board.pieces_for_color[side.index()];
+            if attackers.is_empty() {
+                break;
+            }
+
+            let mut next = None;
+            for p in [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King] {
+                let candidates = attackers & board.pieces[side.index()][p.index()];
+                if !candidates.is_empty() {
+                    next = Some((p, candidates.lsb()));
+                    break;
+                }
+            }
+
+            let (p, square) = next.unwrap();
+            from_bb = Square::from_usize(square).as_bb();
+            attacker_value = Self::SEE_VALUES[p.index()];
+        }
+
+        while depth > 1 {
+            depth -= 1;
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        }
+
+        return gain[0];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_move(board: &BoardState, from: Square, to: Square) -> Move {
+        return MoveProvider::INSTANCE.legal_moves(board).into_iter()
+            .find(|m| m.get_from() == from && m.get_to() == to)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_static_exchange() {
+        // pawn defended by pawn
+        let board = BoardState::from_fen("4k3/8/3p4/4p3/8/8/7Q/4K3 w - - 0 1").unwrap();
+        assert_eq!(100 - 900, MoveOrdering::static_exchange(&board, find_move(&board, Square::H2, Square::E5)));
+
+        // undefended knight
+        let board = BoardState::from_fen("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(325, MoveOrdering::static_exchange(&board, find_move(&board, Square::E4, Square::D5)));
+
+        // rook x-rays through rook on the same file
+        let board = BoardState::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(100, MoveOrdering::static_exchange(&board, find_move(&board, Square::D2, Square::D5)));
+    }
+
+    #[test]
+    fn test_order_moves() {
+        let board = BoardState::from_fen("4k3/8/2p5/3b4/4P3/8/8/3QK3 w - - 0 1").unwrap();
+        let ordering = MoveOrdering::new(8);
+        let moves = MoveProvider::INSTANCE.legal_moves(&board);
+        let hash_move = find_move(&board, Square::D1, Square::D2);
+
+        let ordered = ordering.order_moves(&board, moves, Some(hash_move), 0, None);
+        assert_eq!(hash_move, ordered[0]);
+        assert_eq!(find_move(&board, Square::E4, Square::D5), ordered[1]);
+        assert_eq!(find_move(&board, Square::D1, Square::D5), *ordered.last().unwrap());
+    }
+}
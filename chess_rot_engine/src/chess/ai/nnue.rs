@@ -0,0 +1,323 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, Color, GameError, Piece};
+use crate::chess::ai::evaluate::Evaluate;
+use crate::chess::ai::skill_level::SkillLevel;
+
+/// Network with 768 inputs, piece of a color on a square as seen by one side, one hidden layer
+/// computed for both sides and a single output. Weights are quantized to integers.
+/// Reference: https://www.chessprogramming.org/NNUE
+#[derive(Debug, PartialEq)]
+pub struct Network {
+    hidden: usize,
+    // Column of hidden weights for every input, so a feature updates a contiguous slice
+    feature_weights: Vec<i16>,
+    feature_bias: Vec<i16>,
+    // Hidden layer of the side on move followed by the other side
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+impl Network {
+    pub const INPUTS: usize = 768;
+    // Hidden values are clipped to 0..QA, output weights are scaled by QB
+    const QA: i32 = 255;
+    const QB: i32 = 64;
+    const SCALE: i32 = 400;
+    const MAGIC: &'static [u8; 4] = b"CRNN";
+    const VERSION: u32 = 1;
+
+    /// Network with small random weights, starting point for training and for tests.
+    pub fn random(hidden: usize, seed: u64) -> Self {
+        let mut state = seed.max(1);
+        let mut next = |range: i16| {
+            state = SkillLevel::next_random(state);
+            return (state % (2 * range as u64 + 1)) as i16 - range;
+        };
+        return Self {
+            hidden,
+            feature_weights: (0..Self::INPUTS * hidden).map(|_| next(32)).collect(),
+            feature_bias: (0..hidden).map(|_| next(16)).collect(),
+            output_weights: (0..2 * hidden).map(|_| next(64)).collect(),
+            output_bias: next(1000) as i32,
+        };
+    }
+
+    /// Little endian file: magic, version, hidden size, hidden weights and biases, output weights and bias.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GameError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(4)? != Self::MAGIC || reader.u32()? != Self::VERSION {
+            return Err(GameError::ConfigError("unsupported network format".to_string()));
+        }
+
+        let hidden = reader.u32()? as usize;
+        let network = Self {
+            hidden,
+            feature_weights: reader.i16s(Self::INPUTS * hidden)?,
+            feature_bias: reader.i16s(hidden)?,
+            output_weights: reader.i16s(2 * hidden)?,
+            output_bias: reader.u32()? as i32,
+        };
+        if reader.position != bytes.len() {
+            return Err(GameError::ConfigError("unexpected data after network".to_string()));
+        }
+        return Ok(network);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Self::MAGIC.to_vec();
+        bytes.extend(Self::VERSION.to_le_bytes());
+        bytes.extend((self.hidden as u32).to_le_bytes());
+        for value in self.feature_weights.iter().chain(&self.feature_bias).chain(&self.output_weights) {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(self.output_bias.to_le_bytes());
+        return bytes;
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        let bytes = fs::read(path)
+            .map_err(|e| GameError::ConfigError(format!("cannot read {}: {}", path.display(), e)))?;
+        return Self::from_bytes(&bytes);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        return fs::write(path, self.to_bytes())
+            .map_err(|e| GameError::ConfigError(format!("cannot write {}: {}", path.display(), e)));
+    }
+
+    /// Input of the piece as seen by the perspective, its own pieces first and the board flipped for black.
+    fn feature(perspective: Color, color: Color, piece: Piece, square: usize) -> usize {
+        let (side, square) = match perspective {
+            Color::White => (color.index(), square),
+            Color::Black => (color.inverse().index(), square ^ 56),
+        };
+        return side * 384 + piece.index() * 64 + square;
+    }
+
+    fn column(&self, feature: usize) -> &[i16] {
+        return &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden];
+    }
+
+    /// Computes the accumulator from scratch.
+    fn refresh(&self, accumulator: &mut Accumulator, board: &BoardState) {
+        accumulator.pieces = board.pieces;
+        for perspective in [Color::White, Color::Black] {
+            let values = &mut accumulator.values[perspective.index()];
+            values.copy_from_slice(&self.feature_bias);
+            for color in [Color::White, Color::Black] {
+                for piece in Piece::LIST {
+                    let mut iter = board.pieces[color.index()][piece.index()];
+                    while !iter.is_empty() {
+                        let square = iter.lsb();
+                        iter = iter.remove_bit(square as u64);
+                        Self::add(values, self.column(Self::feature(perspective, color, piece, square)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Updates the accumulator of the parent position by the pieces that differ in the child.
+    fn update(&self, accumulator: &mut Accumulator, child: &BoardState) {
+        for color in [Color::White, Color::Black] {
+            for piece in Piece::LIST {
+                let before = accumulator.pieces[color.index()][piece.index()];
+                let after = child.pieces[color.index()][piece.index()];
+                for (mut iter, added) in [(before & !after, false), (after & !before, true)] {
+                    while !iter.is_empty() {
+                        let square = iter.lsb();
+                        iter = iter.remove_bit(square as u64);
+                        for perspective in [Color::White, Color::Black] {
+                            let column = self.column(Self::feature(perspective, color, piece, square));
+                            let values = &mut accumulator.values[perspective.index()];
+                            if added { Self::add(values, column) } else { Self::sub(values, column) }
+                        }
+                    }
+                }
+            }
+        }
+        accumulator.pieces = child.pieces;
+    }
+
+    /// Evaluation from the perspective of the side on move.
+    fn output(&self, accumulator: &Accumulator, on_move: Color) -> i32 {
+        let us = &accumulator.values[on_move.index()];
+        let them = &accumulator.values[on_move.inverse().index()];
+        let (us_weights, them_weights) = self.output_weights.split_at(self.hidden);
+        let sum = Self::clipped_dot(us, us_weights) + Self::clipped_dot(them, them_weights);
+        return (sum + self.output_bias) * Self::SCALE / (Self::QA * Self::QB);
+    }
+
+    // Plain loops over slices of equal length, compilers turn them into SIMD instructions
+    fn add(values: &mut [i16], column: &[i16]) {
+        for (value, weight) in values.iter_mut().zip(column) {
+            *value = value.wrapping_add(*weight);
+        }
+    }
+
+    fn sub(values: &mut [i16], column: &[i16]) {
+        for (value, weight) in values.iter_mut().zip(column) {
+            *value = value.wrapping_sub(*weight);
+        }
+    }
+
+    fn clipped_dot(values: &[i16], weights: &[i16]) -> i32 {
+        return values.iter().zip(weights)
+            .map(|(value, weight)| (*value as i32).clamp(0, Self::QA) * *weight as i32)
+            .sum();
+    }
+}
+
+/// Hidden layer of both perspectives for the pieces it was computed from.
+#[derive(Debug, Clone)]
+struct Accumulator {
+    pieces: [[BitBoard; 6]; 2],
+    values: [Vec<i16>; 2],
+}
+
+impl Accumulator {
+    fn new(hidden: usize) -> Self {
+        return Self { pieces: [[BitBoard::empty(); 6]; 2], values: [vec![0; hidden], vec![0; hidden]] };
+    }
+}
+
+/// Neural evaluator, follows the search with a stack of accumulators so a move updates only
+/// the inputs of the pieces it changes.
+#[derive(Debug, Clone)]
+pub struct NnueEvaluator {
+    network: Arc<Network>,
+    // Accumulators of positions on the searched line, entries above depth are reused
+    stack: Vec<Accumulator>,
+    depth: usize,
+}
+
+impl NnueEvaluator {
+    pub fn new(network: Arc<Network>) -> Self {
+        return Self { network, stack: Vec::new(), depth: 0 };
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        return Ok(Self::new(Arc::new(Network::load(path)?)));
+    }
+
+    /// Evaluation computed from scratch, ignores the accumulator stack.
+    pub fn evaluate_refreshed(&self, board: &BoardState) -> i32 {
+        let mut accumulator = Accumulator::new(self.network.hidden);
+        self.network.refresh(&mut accumulator, board);
+        return self.network.output(&accumulator, board.color_on_move) * board.color_on_move.factor();
+    }
+}
+
+impl Evaluate for NnueEvaluator {
+    fn evaluate(&self, board: &BoardState) -> i32 {
+        return match self.depth.checked_sub(1).map(|top| &self.stack[top]) {
+            Some(top) if top.pieces == board.pieces => {
+                self.network.output(top, board.color_on_move) * board.color_on_move.factor()
+            }
+            _ => self.evaluate_refreshed(board),
+        };
+    }
+
+    fn push(&mut self, parent: &BoardState, child: &BoardState) {
+        if self.stack.len() == self.depth {
+            self.stack.push(Accumulator::new(self.network.hidden));
+        }
+
+        let (line, rest) = self.stack.split_at_mut(self.depth);
+        let next = &mut rest[0];
+        match line.last() {
+            Some(top) if top.pieces == parent.pieces => {
+                next.clone_from(top);
+                self.network.update(next, child);
+            }
+            _ => self.network.refresh(next, child),
+        }
+        self.depth += 1;
+    }
+
+    fn pop(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn name(&self) -> &'static str {
+        return "Neural";
+    }
+
+    fn box_clone(&self) -> Box<dyn Evaluate> {
+        return Box::new(self.clone());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], GameError> {
+        let slice = self.bytes.get(self.position..self.position + count)
+            .ok_or(GameError::ConfigError("network file is truncated".to_string()))?;
+        self.position += count;
+        return Ok(slice);
+    }
+
+    fn u32(&mut self) -> Result<u32, GameError> {
+        return Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+    }
+
+    fn i16s(&mut self, count: usize) -> Result<Vec<i16>, GameError> {
+        return Ok(self.take(count * 2)?.chunks(2).map(|c| i16::from_le_bytes([c[0], c[1]])).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::move_provider::MoveProvider;
+
+    #[test]
+    fn test_incremental_matches_refresh() {
+        // Castling, en passant, promotions with capture and checks on the way
+        let games = [
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 6),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 8),
+            ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 6),
+        ];
+        let mut evaluator = NnueEvaluator::new(Arc::new(Network::random(32, 7)));
+        for (fen, plies) in games {
+            let mut line = vec![BoardState::from_fen(fen).unwrap()];
+            for ply in 0..plies {
+                let board = *line.last().unwrap();
+                let moves = MoveProvider::INSTANCE.legal_moves(&board);
+                if moves.is_empty() {
+                    break;
+                }
+                // Prefer captures and promotions, they change most inputs
+                let m = moves.iter().copied()
+                    .find(|m| board.is_capture(*m) || board.is_promotion(*m))
+                    .unwrap_or(moves[ply % moves.len()]);
+                let child = board.make_move(m);
+                evaluator.push(&board, &child);
+                assert_eq!(evaluator.evaluate_refreshed(&child), evaluator.evaluate(&child), "{} {}", fen, ply);
+                line.push(child);
+            }
+
+            for board in line.iter().rev().skip(1) {
+                evaluator.pop();
+                assert_eq!(evaluator.evaluate_refreshed(board), evaluator.evaluate(board));
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_file_round_trip() {
+        let network = Network::random(8, 3);
+        let bytes = network.to_bytes();
+        assert_eq!(network, Network::from_bytes(&bytes).unwrap());
+        assert!(Network::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Network::from_bytes(b"NOPE").is_err());
+    }
+}
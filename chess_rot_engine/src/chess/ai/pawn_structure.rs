@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, Color, Piece};
+
+/// Values of the pawn structure terms as (middle game, end game) pairs or tables indexed by
+/// rank relative to the color.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PawnParameters {
+    pub doubled: (i32, i32),
+    pub backward: (i32, i32),
+    pub connected: [i32; 8],
+    pub passed_middle_game: [i32; 8],
+    pub passed_end_game: [i32; 8],
+    pub passed_free_path: [i32; 8],
+    // Per square of king distance from the square in front of a passed pawn in the end game
+    pub enemy_king_distance: i32,
+    pub own_king_distance: i32,
+}
+
+impl PawnParameters {
+    pub const DEFAULT: Self = Self {
+        doubled: (-10, -25),
+        backward: (-8, -12),
+        connected: [0, 5, 8, 12, 25, 45, 80, 0],
+        passed_middle_game: [0, 5, 10, 15, 30, 50, 80, 0],
+        passed_end_game: [0, 10, 15, 25, 50, 90, 140, 0],
+        passed_free_path: [0, 0, 5, 10, 20, 35, 60, 0],
+        enemy_king_distance: 5,
+        own_king_distance: 2,
+    };
+}
+
+impl Default for PawnParameters {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+/// Pawn structure terms that depend only on placement of pawns, so they can be cached by pawn key.
+/// Reference: https://www.chessprogramming.org/Pawn_Structure
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PawnStructure {
+    // Indexed by color, from the perspective of that color
+    pub middle_game: [i32; 2],
+    pub end_game: [i32; 2],
+    // Passed pawns of both colors, used for terms depending on the pieces
+    pub passed: [BitBoard; 2],
+}
+
+impl PawnStructure {
+    pub fn evaluate(board: &BoardState, params: &PawnParameters, isolated_pawn: i32) -> Self {
+        let (white_mg, white_eg, white_passed) = Self::side(Color::White, board, params, isolated_pawn);
+        let (black_mg, black_eg, black_passed) = Self::side(Color::Black, board, params, isolated_pawn);
+        return Self {
+            middle_game: [white_mg, black_mg],
+            end_game: [white_eg, black_eg],
+            passed: [white_passed, black_passed],
+        };
+    }
+
+    fn side(color: Color, board: &BoardState, params: &PawnParameters, isolated_pawn: i32) -> (i32, i32, BitBoard) {
+        let own = board.pieces[color.index()][Piece::Pawn.index()];
+        let enemy = board.pieces[color.inverse().index()][Piece::Pawn.index()];
+        let mut middle_game = 0;
+        let mut end_game = 0;
+        let mut passed = BitBoard::empty();
+
+        let mut iter = own;
+        while !iter.is_empty() {
+            let square = iter.lsb();
+            iter = iter.remove_bit(square as u64);
+            let (file, rank) = (square % 8, square / 8);
+            let relative_rank = Self::relative_rank(color, square);
+            let adjacent = BitBoard::adjacent_files(file);
+            let front = Self::ranks_in_front(color, rank);
+
+            let isolated = (own & adjacent).is_empty();
+            let doubled = !(own & BitBoard::file(file) & front).is_empty();
+            let phalanx = !(own & adjacent & Self::rank(rank)).is_empty();
+            let supported = !(own & adjacent & Self::rank(Self::behind(color, rank))).is_empty();
+            let is_passed = !doubled && (enemy & (BitBoard::file(file) | adjacent) & front).is_empty();
+
+            if isolated {
+                middle_game += isolated_pawn;
+                end_game += isolated_pawn;
+            }
+
+            if doubled {
+                middle_game += params.doubled.0;
+                end_game += params.doubled.1;
+            }
+
+            if phalanx || supported {
+                middle_game += params.connected[relative_rank];
+                end_game += params.connected[relative_rank] / 2;
+            }
+
+            if is_passed {
+                passed = passed | BitBoard::from(1u64 << square);
+                middle_game += params.passed_middle_game[relative_rank];
+                end_game += params.passed_end_game[relative_rank];
+            } else if !isolated && Self::is_backward(color, own, enemy, file, rank) {
+                middle_game += params.backward.0;
+                end_game += params.backward.1;
+            }
+        }
+        return (middle_game, end_game, passed);
+    }
+
+    /// Neighbours are all in front of the pawn and enemy pawn controls the square in front of it.
+    fn is_backward(color: Color, own: BitBoard, enemy: BitBoard, file: usize, rank: usize) -> bool {
+        let adjacent = BitBoard::adjacent_files(file);
+        let behind_or_level = !Self::ranks_in_front(color, rank);
+        if !(own & adjacent & behind_or_level).is_empty() {
+            return false;
+        }
+
+        let stop = Self::ahead(color, rank);
+        if stop == Self::ahead(color, stop) {
+            return false;
+        }
+        return !(enemy & adjacent & Self::rank(Self::ahead(color, stop))).is_empty();
+    }
+
+    /// Terms of passed pawns of the color depending on other pieces, blocked or free path and king distance.
+    pub fn passed_pawns(&self, color: Color, board: &BoardState, params: &PawnParameters) -> (i32, i32) {
+        let occupied = board.pieces_for_color[0] | board.pieces_for_color[1];
+        let own_king = board.pieces[color.index()][Piece::King.index()].lsb();
+        let enemy_king = board.pieces[color.inverse().index()][Piece::King.index()].lsb();
+        let mut middle_game = 0;
+        let mut end_game = 0;
+        let mut iter = self.passed[color.index()];
+        while !iter.is_empty() {
+            let square = iter.lsb();
+            iter = iter.remove_bit(square as u64);
+            let relative_rank = Self::relative_rank(color, square);
+            let stop = Self::ahead(color, square / 8) * 8 + square % 8;
+
+            if occupied.is_bit_set(stop as u64) {
+                middle_game -= params.passed_middle_game[relative_rank] / 2;
+                end_game -= params.passed_end_game[relative_rank] / 2;
+            } else if (occupied & BitBoard::file(square % 8) & Self::ranks_in_front(color, square / 8)).is_empty() {
+                end_game += params.passed_free_path[relative_rank];
+            }
+
+            // Kings matter only for pawns far enough to be a threat
+            let weight = relative_rank.saturating_sub(2) as i32;
+            end_game += weight * (params.enemy_king_distance * distance(enemy_king, stop)
+                - params.own_king_distance * distance(own_king, stop));
+        }
+        return (middle_game, end_game);
+    }
+
+    fn relative_rank(color: Color, square: usize) -> usize {
+        return match color {
+            Color::White => square / 8,
+            Color::Black => 7 - square / 8,
+        };
+    }
+
+    fn ranks_in_front(color: Color, rank: usize) -> BitBoard {
+        return match color {
+            Color::White => BitBoard::ranks_above(rank),
+            Color::Black => BitBoard::ranks_below(rank),
+        };
+    }
+
+    fn ahead(color: Color, rank: usize) -> usize {
+        return match color {
+            Color::White => (rank + 1).min(7),
+            Color::Black => rank.saturating_sub(1),
+        };
+    }
+
+    fn behind(color: Color, rank: usize) -> usize {
+        return Self::ahead(color.inverse(), rank);
+    }
+
+    fn rank(rank: usize) -> BitBoard {
+        return BitBoard::from(BitBoard::RANK_1.raw() << (rank * 8));
+    }
+}
+
+/// Chebyshev distance of two squares, number of king moves between them.
+pub fn distance(from: usize, to: usize) -> i32 {
+    let files = (from % 8) as i32 - (to % 8) as i32;
+    let ranks = (from / 8) as i32 - (to / 8) as i32;
+    return files.abs().max(ranks.abs());
+}
+
+/// Cache of pawn structure, every search thread has its own copy so it is not synchronized.
+#[derive(Debug, Clone)]
+pub struct PawnHashTable {
+    entries: Vec<Option<(u64, PawnStructure)>>,
+}
+
+impl PawnHashTable {
+    pub const DEFAULT_ENTRIES: usize = 1 << 14;
+
+    pub fn new(entries: usize) -> Self {
+        return Self { entries: vec![None; entries.max(1)] };
+    }
+
+    pub fn probe(&self, key: u64) -> Option<PawnStructure> {
+        return match self.entries[key as usize % self.entries.len()] {
+            Some((stored, structure)) if stored == key => Some(structure),
+            _ => None,
+        };
+    }
+
+    pub fn store(&mut self, key: u64, structure: PawnStructure) {
+        let index = key as usize % self.entries.len();
+        self.entries[index] = Some((key, structure));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(fen: &str) -> PawnStructure {
+        return PawnStructure::evaluate(&BoardState::from_fen(fen).unwrap(), &PawnParameters::DEFAULT, -15);
+    }
+
+    #[test]
+    fn test_passed_pawns() {
+        let board = BoardState::from_fen("4k3/8/1p6/8/P2P3P/8/6p1/4K3 w - - 0 1").unwrap();
+        let structure = PawnStructure::evaluate(&board, &PawnParameters::DEFAULT, -15);
+        let white: Vec<usize> = (0..64).filter(|s| structure.passed[0].is_bit_set(*s as u64)).collect();
+        let black: Vec<usize> = (0..64).filter(|s| structure.passed[1].is_bit_set(*s as u64)).collect();
+        // a4 is stopped by b6, d4 and h4 are free, g2 is passed for black
+        assert_eq!(vec![27, 31], white);
+        assert_eq!(vec![14], black);
+    }
+
+    #[test]
+    fn test_structure_penalties() {
+        let healthy = structure("4k3/8/8/8/8/8/PPP5/4K3 w - - 0 1");
+        let isolated = structure("4k3/8/8/8/8/8/P1P5/4K3 w - - 0 1");
+        let doubled = structure("4k3/8/8/8/8/P7/P1P5/4K3 w - - 0 1");
+        assert!(healthy.middle_game[0] > isolated.middle_game[0]);
+        assert!(isolated.end_game[0] > doubled.end_game[0]);
+
+        // d3 can not be supported by c4 and e4 and d4 is attacked by e5
+        let board = BoardState::from_fen("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        let (own, enemy) = (board.pieces[0][Piece::Pawn.index()], board.pieces[1][Piece::Pawn.index()]);
+        assert!(PawnStructure::is_backward(Color::White, own, enemy, 3, 2));
+        assert!(!PawnStructure::is_backward(Color::White, own | BitBoard::from(1 << 10), enemy, 3, 2));
+    }
+
+    #[test]
+    fn test_pawn_hash_table() {
+        let board = BoardState::default();
+        let mut table = PawnHashTable::new(16);
+        assert_eq!(None, table.probe(board.pawn_key()));
+        let structure = PawnStructure::evaluate(&board, &PawnParameters::DEFAULT, -15);
+        table.store(board.pawn_key(), structure);
+        assert_eq!(Some(structure), table.probe(board.pawn_key()));
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::chess::ai::evaluation_parameters::{load_config, save_config};
+use crate::chess::ai::evaluator::{EvaluationWeights, Evaluator};
+use crate::chess::GameError;
+
+/// Named playing style, evaluation weights together with contempt for draws.
+/// Stored in TOML or JSON files like evaluation parameters, missing values keep the balanced defaults.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Personality {
+    pub name: String,
+    pub weights: EvaluationWeights,
+    // Centipawns the engine loses by accepting a draw, negative values make it seek draws
+    pub contempt: i32,
+}
+
+impl Personality {
+    pub fn balanced() -> Self {
+        return Self { name: "Balanced".to_string(), weights: EvaluationWeights::new(), contempt: 0 };
+    }
+
+    pub fn aggressive() -> Self {
+        let weights = EvaluationWeights { castling: 30, mobility: 120, king_safety: 80, king_attack: 25, risk: 8, ..EvaluationWeights::new() };
+        return Self { name: "Aggressive".to_string(), weights, contempt: 50 };
+    }
+
+    pub fn positional() -> Self {
+        let weights = EvaluationWeights { material: 90, isolated_pawn: -30, double_bishop: 70, mobility: 130, ..EvaluationWeights::new() };
+        return Self { name: "Positional".to_string(), weights, contempt: 10 };
+    }
+
+    pub fn defensive() -> Self {
+        let weights = EvaluationWeights { castling: 120, king_safety: 150, risk: -8, ..EvaluationWeights::new() };
+        return Self { name: "Defensive".to_string(), weights, contempt: -20 };
+    }
+
+    pub fn material_greedy() -> Self {
+        let weights = EvaluationWeights { material: 120, castling: 20, isolated_pawn: -5, double_bishop: 15, mobility: 70, ..EvaluationWeights::new() };
+        return Self { name: "Material Greedy".to_string(), weights, contempt: 20 };
+    }
+
+    pub fn presets() -> Vec<Personality> {
+        return vec![Self::balanced(), Self::aggressive(), Self::positional(), Self::defensive(), Self::material_greedy()];
+    }
+
+    pub fn evaluator(&self) -> Evaluator {
+        return Evaluator::with_weights(self.weights);
+    }
+
+    pub fn load(path: &Path) -> Result<Personality, GameError> {
+        return load_config(path);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        return save_config(self, path);
+    }
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        return Self::balanced();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for personality in Personality::presets() {
+            assert_eq!(personality, toml::from_str(&toml::to_string(&personality).unwrap()).unwrap());
+            assert_eq!(personality, serde_json::from_str(&serde_json::to_string(&personality).unwrap()).unwrap());
+        }
+
+        let parsed: Personality = toml::from_str("# partial profile\nname = \"Custom\"\n[weights]\nrisk = -3\n").unwrap();
+        assert_eq!("Custom", parsed.name);
+        assert_eq!(-3, parsed.weights.risk);
+        assert_eq!(EvaluationWeights::new().material, parsed.weights.material);
+        assert_eq!(0, parsed.contempt);
+
+        assert!(toml::from_str::<Personality>("contempt = \"high\"").is_err());
+    }
+}
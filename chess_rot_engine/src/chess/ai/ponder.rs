@@ -0,0 +1,97 @@
+use crate::chess::{BoardState, Move};
+use crate::chess::ai::ai_strategy::Minimax;
+use crate::chess::ai::background_search::BackgroundSearch;
+use crate::chess::ai::search_limits::SearchLimits;
+use crate::chess::ai::search_result::SearchResult;
+
+/// Searches position after the expected reply in background while the opponent thinks.
+/// When the opponent plays the expected move the search goes on as the search of the engine's move,
+/// otherwise the engine is handed back and its transposition table speeds up the next search.
+/// Reference: https://www.chessprogramming.org/Pondering
+pub struct Ponder {
+    ponder_move: Move,
+    search: BackgroundSearch,
+}
+
+impl Ponder {
+    /// Starts pondering, board is the position with opponent on move and the last one of the engine history.
+    /// Limits should not restrict time, the clock of the engine does not run while it ponders.
+    pub fn start(mut minimax: Minimax, board: &BoardState, ponder_move: Move, limits: &SearchLimits) -> Ponder {
+        minimax.push_history(board.zobrist_key());
+        let search = BackgroundSearch::start(minimax, &board.make_move(ponder_move), *limits);
+        return Ponder { ponder_move, search };
+    }
+
+    pub fn ponder_move(&self) -> Move {
+        return self.ponder_move;
+    }
+
+    /// Ponder search reached its limits, its result is ready.
+    pub fn is_finished(&self) -> bool {
+        return self.search.is_finished();
+    }
+
+    /// Opponent played the expected move, the running search becomes the search of the engine's move.
+    pub fn hit(self) -> BackgroundSearch {
+        return self.search;
+    }
+
+    /// Ends pondering after the opponent played the move, returns engine and result of the ponder
+    /// search when the expected move was played.
+    pub fn finish(self, played: Move) -> (Minimax, Option<SearchResult>) {
+        let hit = played == self.ponder_move;
+        let (minimax, result) = self.search.join();
+        return (minimax, if hit { result.ok() } else { None });
+    }
+
+    pub fn stop(self) -> Minimax {
+        return self.search.join().0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::ai::evaluator::Evaluator;
+    use crate::chess::move_provider::MoveProvider;
+
+    #[test]
+    fn test_ponder_hit_reuses_transposition_table() {
+        let board = BoardState::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 0 1").unwrap();
+        let reply = MoveProvider::INSTANCE.legal_moves(&board)[0];
+        let after_reply = board.make_move(reply);
+        let limits = SearchLimits::depth(4);
+
+        let fresh_nodes = Minimax::new(Evaluator::new(), 4, 0.0).search_with_limits(&after_reply, &limits).unwrap().nodes;
+
+        let ponder = Ponder::start(Minimax::new(Evaluator::new(), 4, 0.0), &board, reply, &limits);
+        while !ponder.is_finished() {
+            std::thread::yield_now();
+        }
+        let (mut minimax, ponder_result) = ponder.finish(reply);
+        assert_eq!(4, ponder_result.unwrap().depth);
+
+        let nodes = minimax.search_with_limits(&after_reply, &limits).unwrap().nodes;
+        assert!(nodes < fresh_nodes, "ponder hit {} >= fresh {}", nodes, fresh_nodes);
+    }
+
+    #[test]
+    fn test_ponder_hit_continues_search() {
+        let board = BoardState::default();
+        let reply = MoveProvider::INSTANCE.legal_moves(&board)[0];
+        let ponder = Ponder::start(Minimax::new(Evaluator::new(), 3, 0.0), &board, reply, &SearchLimits::depth(3));
+        let search = ponder.hit();
+        assert_eq!(board.make_move(reply).zobrist_key(), search.board().zobrist_key());
+        let (_, result) = search.wait();
+        assert_eq!(3, result.unwrap().depth);
+    }
+
+    #[test]
+    fn test_ponder_miss() {
+        let board = BoardState::default();
+        let moves = MoveProvider::INSTANCE.legal_moves(&board);
+        let ponder = Ponder::start(Minimax::new(Evaluator::new(), 4, 0.0), &board, moves[0], &SearchLimits::infinite());
+        let (_, ponder_result) = ponder.finish(moves[1]);
+        assert!(ponder_result.is_none());
+    }
+}
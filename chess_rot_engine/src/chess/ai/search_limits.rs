@@ -0,0 +1,77 @@
+use std::time::Duration;
+use crate::chess::Color;
+
+/// Conditions that end a search, mirrors parameters of the UCI go command.
+/// Search without any limit runs until it is stopped.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    // Exact time for the move in milliseconds
+    pub movetime: Option<u64>,
+    // Remaining clock time and increment per move in milliseconds
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: u64,
+    pub binc: u64,
+    pub movestogo: Option<u32>,
+    // Search for mate in given number of moves
+    pub mate: Option<u32>,
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    // Moves left in the game assumed when there is no movestogo
+    const DEFAULT_MOVES_TO_GO: u64 = 30;
+    // Time kept on the clock for overhead of making the move
+    const MOVE_OVERHEAD_MS: u64 = 50;
+
+    pub fn depth(depth: usize) -> Self {
+        return Self { depth: Some(depth), ..Self::default() };
+    }
+
+    pub fn movetime(movetime: u64) -> Self {
+        return Self { movetime: Some(movetime), ..Self::default() };
+    }
+
+    pub fn infinite() -> Self {
+        return Self { infinite: true, ..Self::default() };
+    }
+
+    /// Time that can be spent on the move by the color, None when time is not limited.
+    pub fn time_budget(&self, color: Color) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+
+        if let Some(movetime) = self.movetime {
+            return Some(Duration::from_millis(movetime));
+        }
+
+        let (time, increment) = match color {
+            Color::White => (self.wtime?, self.winc),
+            Color::Black => (self.btime?, self.binc),
+        };
+        let moves_to_go = self.movestogo.map_or(Self::DEFAULT_MOVES_TO_GO, |m| m.max(1) as u64);
+        let available = time.saturating_sub(Self::MOVE_OVERHEAD_MS);
+        let budget = (time / moves_to_go + increment * 3 / 4).min(available);
+        return Some(Duration::from_millis(budget.max(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_budget() {
+        assert_eq!(None, SearchLimits::depth(5).time_budget(Color::White));
+        assert_eq!(Some(Duration::from_millis(300)), SearchLimits::movetime(300).time_budget(Color::Black));
+
+        let limits = SearchLimits { wtime: Some(60_000), btime: Some(1_000), winc: 1_000, binc: 1_000, ..SearchLimits::default() };
+        assert_eq!(Some(Duration::from_millis(2_750)), limits.time_budget(Color::White));
+        // never more than what is left on the clock
+        let limits = SearchLimits { btime: Some(100), binc: 1_000, movestogo: Some(1), ..SearchLimits::default() };
+        assert_eq!(Some(Duration::from_millis(50)), limits.time_budget(Color::Black));
+    }
+}
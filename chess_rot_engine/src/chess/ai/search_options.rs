@@ -0,0 +1,80 @@
+/// Switches for the selective parts of the search, so they can be compared against each other.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SearchOptions {
+    pub quiescence: bool,
+    pub delta_pruning: bool,
+    pub move_ordering: bool,
+    pub transposition_table: bool,
+    pub null_move_pruning: bool,
+    pub late_move_reductions: bool,
+    pub futility_pruning: bool,
+    pub reverse_futility_pruning: bool,
+    pub check_extensions: bool,
+    pub principal_variation_search: bool,
+    pub aspiration_windows: bool,
+    pub mate_distance_pruning: bool,
+}
+
+impl SearchOptions {
+    pub const fn new() -> Self {
+        return Self {
+            quiescence: true,
+            delta_pruning: true,
+            move_ordering: true,
+            transposition_table: true,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            futility_pruning: true,
+            reverse_futility_pruning: true,
+            check_extensions: true,
+            principal_variation_search: true,
+            aspiration_windows: true,
+            mate_distance_pruning: true,
+        };
+    }
+
+    /// Plain alpha-beta in generator order that evaluates leaf nodes directly.
+    pub const fn full_width() -> Self {
+        return Self {
+            quiescence: false,
+            delta_pruning: false,
+            move_ordering: false,
+            transposition_table: false,
+            null_move_pruning: false,
+            late_move_reductions: false,
+            futility_pruning: false,
+            reverse_futility_pruning: false,
+            check_extensions: false,
+            principal_variation_search: false,
+            aspiration_windows: false,
+            mate_distance_pruning: false,
+        };
+    }
+
+    /// Switches option by its field name, returns false for unknown names.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        let option = match name {
+            "quiescence" => &mut self.quiescence,
+            "delta_pruning" => &mut self.delta_pruning,
+            "move_ordering" => &mut self.move_ordering,
+            "transposition_table" => &mut self.transposition_table,
+            "null_move_pruning" => &mut self.null_move_pruning,
+            "late_move_reductions" => &mut self.late_move_reductions,
+            "futility_pruning" => &mut self.futility_pruning,
+            "reverse_futility_pruning" => &mut self.reverse_futility_pruning,
+            "check_extensions" => &mut self.check_extensions,
+            "principal_variation_search" => &mut self.principal_variation_search,
+            "aspiration_windows" => &mut self.aspiration_windows,
+            "mate_distance_pruning" => &mut self.mate_distance_pruning,
+            _ => return false,
+        };
+        *option = enabled;
+        return true;
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
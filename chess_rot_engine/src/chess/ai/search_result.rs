@@ -0,0 +1,71 @@
+use std::fmt;
+use crate::chess::{BoardState, Move};
+
+/// Score from the perspective of the side on move.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Score {
+    Centipawns(i32),
+    // Full moves until mate, negative when side on move is getting mated
+    Mate(i32),
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Score::Centipawns(cp) => write!(f, "cp {}", cp),
+            Score::Mate(moves) => write!(f, "mate {}", moves),
+        };
+    }
+}
+
+/// Root move with its score and the line that is expected to follow.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PvLine {
+    pub score: Score,
+    pub pv: Vec<Move>,
+}
+
+impl PvLine {
+    /// Line in standard algebraic notation, played from the searched position.
+    pub fn to_san(&self, board: &BoardState) -> Vec<String> {
+        let mut board = *board;
+        let mut san = Vec::with_capacity(self.pv.len());
+        for m in self.pv.iter() {
+            san.push(board.to_san(*m));
+            board = board.make_move(*m);
+        }
+        return san;
+    }
+}
+
+/// Outcome of one iterative deepening iteration, the last one is the result of the search.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchResult {
+    pub best_move: Move,
+    pub score: Score,
+    pub pv: Vec<Move>,
+    // Best lines sorted from the best one, more than one only in Multi-PV mode
+    pub lines: Vec<PvLine>,
+    pub depth: usize,
+    pub seldepth: usize,
+    pub nodes: u64,
+    pub nps: u64,
+    // Permille of used transposition table entries
+    pub hashfull: usize,
+    pub time_ms: u128,
+}
+
+impl SearchResult {
+    /// Principal variation in standard algebraic notation, played from the searched position.
+    pub fn pv_san(&self, board: &BoardState) -> Vec<String> {
+        return self.lines[0].to_san(board);
+    }
+}
+
+impl fmt::Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pv: Vec<String> = self.pv.iter().map(|m| m.to_uci()).collect();
+        return write!(f, "depth {} seldepth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+                      self.depth, self.seldepth, self.score, self.nodes, self.nps, self.hashfull, self.time_ms, pv.join(" "));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::chess::Move;
+use crate::chess::ai::search_limits::SearchLimits;
+use crate::chess::ai::search_result::{Score, SearchResult};
+
+/// Strength of the engine from 0 (beginner) to 20 (full strength).
+/// Weaker levels search fewer nodes, add noise to the evaluation and sometimes
+/// choose a worse move out of Multi-PV lines.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SkillLevel {
+    level: u8,
+}
+
+impl SkillLevel {
+    pub const MAX: u8 = 20;
+    // Rough estimate of rating for level 0 and points added by every level
+    const BASE_ELO: u32 = 600;
+    const ELO_PER_LEVEL: u32 = 80;
+    const BASE_NODES: f64 = 50.0;
+    const NODES_GROWTH: f64 = 1.6;
+    const NOISE_PER_LEVEL: i32 = 10;
+    const MULTI_PV: usize = 4;
+
+    pub fn new(level: u8) -> Self {
+        return Self { level: level.min(Self::MAX) };
+    }
+
+    pub const fn full_strength() -> Self {
+        return Self { level: Self::MAX };
+    }
+
+    /// Level closest to approximate Elo rating.
+    pub fn from_elo(elo: u32) -> Self {
+        let level = (elo.saturating_sub(Self::BASE_ELO) + Self::ELO_PER_LEVEL / 2) / Self::ELO_PER_LEVEL;
+        return Self::new(level.min(Self::MAX as u32) as u8);
+    }
+
+    pub fn level(&self) -> u8 {
+        return self.level;
+    }
+
+    pub fn elo(&self) -> u32 {
+        return Self::BASE_ELO + self.level as u32 * Self::ELO_PER_LEVEL;
+    }
+
+    pub fn is_full_strength(&self) -> bool {
+        return self.level == Self::MAX;
+    }
+
+    /// Limits added to the limits of the search, full strength adds none.
+    pub fn limits(&self) -> SearchLimits {
+        if self.is_full_strength() {
+            return SearchLimits::default();
+        }
+
+        let nodes = Self::BASE_NODES * Self::NODES_GROWTH.powi(self.level as i32);
+        return SearchLimits {
+            depth: Some(2 + self.level as usize / 2),
+            nodes: Some(nodes as u64),
+            ..SearchLimits::default()
+        };
+    }
+
+    /// Maximal centipawns added to or subtracted from evaluation of a position.
+    pub fn eval_noise(&self) -> i32 {
+        return (Self::MAX - self.level) as i32 * Self::NOISE_PER_LEVEL;
+    }
+
+    pub fn multi_pv(&self) -> usize {
+        return if self.is_full_strength() { 1 } else { Self::MULTI_PV };
+    }
+
+    /// Picks one of the searched lines, worse lines get a random bonus that grows with weakness.
+    /// Reference: skill level of Stockfish https://github.com/official-stockfish/Stockfish
+    pub fn pick_move(&self, result: &SearchResult, random: u64) -> Move {
+        if self.is_full_strength() || result.lines.len() < 2 {
+            return result.best_move;
+        }
+
+        let weakness = 120 - 2 * self.level as i64;
+        let scores: Vec<i64> = result.lines.iter().map(|l| Self::centipawns(l.score)).collect();
+        let top = scores[0];
+        let delta = (top - scores[scores.len() - 1]).min(100);
+
+        let mut random = random;
+        let mut best = (i64::MIN, result.best_move);
+        for (line, score) in result.lines.iter().zip(scores) {
+            random = Self::next_random(random);
+            let push = (weakness * (top - score) + delta * (random % weakness as u64) as i64) / 128;
+            if score + push > best.0 {
+                best = (score + push, line.pv[0]);
+            }
+        }
+        return best.1;
+    }
+
+    fn centipawns(score: Score) -> i64 {
+        return match score {
+            Score::Centipawns(cp) => cp as i64,
+            Score::Mate(moves) if moves > 0 => 100_000 - moves as i64,
+            Score::Mate(moves) => -100_000 - moves as i64,
+        };
+    }
+
+    // xorshift64
+    pub fn next_random(mut state: u64) -> u64 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return state;
+    }
+}
+
+impl Default for SkillLevel {
+    fn default() -> Self {
+        return Self::full_strength();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::BoardState;
+    use crate::chess::ai::search_result::PvLine;
+    use crate::chess::move_provider::MoveProvider;
+
+    #[test]
+    fn test_elo() {
+        assert_eq!(SkillLevel::new(0), SkillLevel::from_elo(0));
+        assert_eq!(SkillLevel::full_strength(), SkillLevel::from_elo(5000));
+        for level in 0..=SkillLevel::MAX {
+            assert_eq!(level, SkillLevel::from_elo(SkillLevel::new(level).elo()).level());
+        }
+    }
+
+    #[test]
+    fn test_pick_move() {
+        let board = BoardState::default();
+        let moves = MoveProvider::INSTANCE.legal_moves(&board);
+        let lines: Vec<PvLine> = moves.iter().take(4).enumerate()
+            .map(|(i, m)| PvLine { score: Score::Centipawns(30 - i as i32 * 20), pv: vec![*m] })
+            .collect();
+        let result = SearchResult {
+            best_move: moves[0], score: lines[0].score, pv: lines[0].pv.clone(), lines,
+            depth: 1, seldepth: 1, nodes: 0, nps: 0, hashfull: 0, time_ms: 0,
+        };
+
+        let mut random = 0x2545f4914f6cdd1d;
+        let mut picked = Vec::new();
+        for _ in 0..50 {
+            random = SkillLevel::next_random(random);
+            assert_eq!(moves[0], SkillLevel::full_strength().pick_move(&result, random));
+            picked.push(SkillLevel::new(0).pick_move(&result, random));
+        }
+        assert!(picked.iter().any(|m| *m != moves[0]));
+        assert!(picked.iter().all(|m| moves[..4].contains(m)));
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use crate::chess::{BoardState, Move};
+use crate::chess::move_provider::MoveProvider;
+
+/// Game theoretical value of a position from the perspective of the side on move.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Wdl {
+    pub fn inverse(self) -> Wdl {
+        return match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::Win => Wdl::Loss,
+        };
+    }
+}
+
+/// Endgame tables with perfect knowledge of positions with few pieces.
+pub trait Tablebase: Debug + Send + Sync {
+    /// Largest number of pieces, kings included, of positions that can be probed.
+    fn max_pieces(&self) -> u32;
+
+    fn probe_wdl(&self, board: &BoardState) -> Option<Wdl>;
+
+    /// Plies until the game is won or lost with perfect play, positive when the side on move wins
+    /// and zero for draws. Tables may count plies to mate or to the next capture or pawn move.
+    fn probe_dtz(&self, board: &BoardState) -> Option<i32>;
+}
+
+/// Tablebase used by the search, positions with more pieces than the limit are not probed
+/// and inside of the tree only nodes with at least the probe depth remaining are probed.
+#[derive(Debug, Clone)]
+pub struct TablebaseProbe {
+    pub tablebase: Arc<dyn Tablebase>,
+    pub probe_limit: u32,
+    pub probe_depth: usize,
+}
+
+impl TablebaseProbe {
+    pub const DEFAULT_PROBE_DEPTH: usize = 1;
+
+    pub fn new(tablebase: Arc<dyn Tablebase>) -> Self {
+        let probe_limit = tablebase.max_pieces();
+        return Self { tablebase, probe_limit, probe_depth: Self::DEFAULT_PROBE_DEPTH };
+    }
+
+    pub fn can_probe(&self, board: &BoardState) -> bool {
+        let pieces = board.all_pieces().bit_count();
+        return pieces <= self.probe_limit && pieces <= self.tablebase.max_pieces();
+    }
+
+    /// Root moves that keep the best outcome, winning moves that take longest to convert and losing
+    /// moves that hold out shortest are dropped. Returns None when the position can not be probed.
+    pub fn root_moves(&self, board: &BoardState, moves: &[Move]) -> Option<Vec<Move>> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let mut ranked = Vec::with_capacity(moves.len());
+        for m in moves {
+            let child = board.make_move(*m);
+            let wdl = match MoveProvider::INSTANCE.legal_moves(&child).is_empty() {
+                true if MoveProvider::INSTANCE.is_in_check(&child) => Wdl::Win,
+                true => Wdl::Draw,
+                false => self.tablebase.probe_wdl(&child)?.inverse(),
+            };
+            // Faster wins and slower losses rank higher, distance is unknown for mates and draws
+            let distance = match wdl {
+                Wdl::Draw => 0,
+                _ => self.tablebase.probe_dtz(&child).map_or(0, |d| d.abs()),
+            };
+            let rank = match wdl {
+                Wdl::Win => -distance,
+                Wdl::Draw => 0,
+                Wdl::Loss => distance,
+            };
+            ranked.push((wdl, rank, *m));
+        }
+
+        let best = ranked.iter().map(|(wdl, rank, _)| (*wdl, *rank)).max()?;
+        return Some(ranked.into_iter().filter(|(wdl, rank, _)| (*wdl, *rank) == best).map(|(_, _, m)| m).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::{Color, Piece};
+    use crate::chess::ai::ai_strategy::Minimax;
+    use crate::chess::ai::evaluator::Evaluator;
+    use crate::chess::ai::search_result::Score;
+
+    // Queen side wins, unless its queen is left next to the lone king without protection
+    #[derive(Debug)]
+    struct QueenEndings;
+
+    impl QueenEndings {
+        fn distance(a: usize, b: usize) -> usize {
+            return (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8));
+        }
+    }
+
+    impl Tablebase for QueenEndings {
+        fn max_pieces(&self) -> u32 {
+            return 3;
+        }
+
+        fn probe_wdl(&self, board: &BoardState) -> Option<Wdl> {
+            match board.all_pieces().bit_count() {
+                2 => return Some(Wdl::Draw),
+                3 => {}
+                _ => return None,
+            }
+            let strong = [Color::White, Color::Black].into_iter()
+                .find(|c| !board.pieces[c.index()][Piece::Queen.index()].is_empty())?;
+            let queen = board.pieces[strong.index()][Piece::Queen.index()].lsb();
+            let hanging = Self::distance(board.get_king(strong.inverse()).lsb(), queen) == 1
+                && Self::distance(board.get_king(strong).lsb(), queen) > 1;
+            return Some(match board.on_move() == strong {
+                true => Wdl::Win,
+                false if hanging => Wdl::Draw,
+                false => Wdl::Loss,
+            });
+        }
+
+        fn probe_dtz(&self, _board: &BoardState) -> Option<i32> {
+            return None;
+        }
+    }
+
+    #[test]
+    fn test_root_moves_keep_win() {
+        let board = BoardState::from_fen("8/8/8/2k5/8/8/8/K2Q4 w - - 0 1").unwrap();
+        let probe = TablebaseProbe::new(Arc::new(QueenEndings));
+        let legal = MoveProvider::INSTANCE.legal_moves(&board);
+        let moves = probe.root_moves(&board, &legal).unwrap();
+        assert!(moves.len() < legal.len());
+        assert!(moves.iter().all(|m| m.to_uci() != "d1d4"));
+        assert!(moves.iter().all(|m| QueenEndings.probe_wdl(&board.make_move(*m)) == Some(Wdl::Loss)));
+
+        let crowded = BoardState::default();
+        assert!(probe.root_moves(&crowded, &MoveProvider::INSTANCE.legal_moves(&crowded)).is_none());
+    }
+
+    #[test]
+    fn test_search_uses_tablebase() {
+        let board = BoardState::from_fen("8/8/8/2k5/8/8/8/K2Q4 w - - 0 1").unwrap();
+        let without = Minimax::new(Evaluator::new(), 3, 0.0).search(&board).unwrap();
+        let mut minimax = Minimax::new(Evaluator::new(), 3, 0.0);
+        minimax.set_tablebase(Some(TablebaseProbe::new(Arc::new(QueenEndings))));
+        let with = minimax.search(&board).unwrap();
+        assert!(matches!(without.score, Score::Centipawns(cp) if cp < 10_000), "{}", without.score);
+        assert!(matches!(with.score, Score::Centipawns(cp) if cp > 10_000), "{}", with.score);
+        assert_ne!("d1d4", with.best_move.to_uci());
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use crate::bitboard::BitBoard;
+use crate::chess::{BoardState, CastlingRight, Color, GameError, Piece, Square};
+use crate::chess::ai::ai_strategy::Minimax;
+use crate::chess::ai::search_limits::SearchLimits;
+use crate::chess::ai::search_result::Score;
+use crate::chess::ai::skill_level::SkillLevel;
+use crate::chess::ai::tuning::TuningPosition;
+use crate::chess::move_provider::MoveProvider;
+
+/// Position from a self-play game with the search score and the game result, both from white's perspective.
+#[derive(Debug, Clone, Copy)]
+pub struct TrainingRecord {
+    pub board: BoardState,
+    pub score: i16,
+    // 1.0 is white win, 0.5 draw and 0.0 black win
+    pub result: f64,
+}
+
+impl TrainingRecord {
+    /// Records are 32 bytes: occupancy, a nibble per occupied square, flags, en passant square,
+    /// half move clock, full move number, score and result, all little endian.
+    /// Every game starts with a header of the same size, see `append_game`.
+    pub const SIZE: usize = 32;
+    const NO_EN_PASSANT: u8 = 64;
+    const BLACK_ON_MOVE: u8 = 0x80;
+    const CASTLING_MASK: u8 = 0x3f;
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        let board = &self.board;
+        let occupancy = board.all_pieces();
+        bytes[0..8].copy_from_slice(&occupancy.raw().to_le_bytes());
+
+        let mut iter = occupancy;
+        let mut index = 0;
+        while !iter.is_empty() {
+            let square = iter.lsb();
+            iter = iter.remove_bit(square as u64);
+            let (piece, color) = board.get_piece_at(square as u64).expect("occupied square has a piece");
+            let nibble = piece.index() as u8 | if color == Color::Black { 0x8 } else { 0 };
+            bytes[8 + index / 2] |= nibble << (4 * (index % 2));
+            index += 1;
+        }
+
+        let mut flags = board.castling.raw() & Self::CASTLING_MASK;
+        if board.on_move() == Color::Black {
+            flags |= Self::BLACK_ON_MOVE;
+        }
+        bytes[24] = flags;
+        bytes[25] = board.en_passant_position.map_or(Self::NO_EN_PASSANT, |s| s.as_usize() as u8);
+        bytes[26] = board.half_move_clock().min(u8::MAX as u16) as u8;
+        bytes[27..29].copy_from_slice(&board.full_moves().to_le_bytes());
+        bytes[29..31].copy_from_slice(&self.score.to_le_bytes());
+        bytes[31] = (self.result * 2.0).round() as u8;
+        return bytes;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<TrainingRecord, GameError> {
+        let invalid = |reason: &str| GameError::ConfigError(format!("invalid training record: {}", reason));
+        if bytes.len() != Self::SIZE {
+            return Err(invalid("wrong size"));
+        }
+
+        let occupancy = BitBoard::from(u64::from_le_bytes(bytes[0..8].try_into().unwrap()));
+        if occupancy.is_empty() {
+            return Err(invalid("game header is not a position"));
+        }
+        if occupancy.bit_count() > 32 {
+            return Err(invalid("too many pieces"));
+        }
+        let mut pieces = [[BitBoard::empty(); 6]; 2];
+        let mut iter = occupancy;
+        let mut index = 0;
+        while !iter.is_empty() {
+            let square = iter.lsb();
+            iter = iter.remove_bit(square as u64);
+            let nibble = (bytes[8 + index / 2] >> (4 * (index % 2))) & 0xf;
+            let piece = Piece::try_from((nibble & 0x7) as usize).map_err(|_| invalid("unknown piece"))?;
+            let color = if nibble & 0x8 != 0 { Color::Black } else { Color::White };
+            pieces[color.index()][piece.index()] |= BitBoard::from(1 << square);
+            index += 1;
+        }
+
+        let flags = bytes[24];
+        let color_on_move = if flags & Self::BLACK_ON_MOVE != 0 { Color::Black } else { Color::White };
+        let en_passant_position = match bytes[25] {
+            Self::NO_EN_PASSANT => None,
+            square if square < 64 => Some(Square::from_usize(square as usize)),
+            _ => return Err(invalid("en passant square out of board")),
+        };
+        let result = match bytes[31] {
+            0 => 0.0,
+            1 => 0.5,
+            2 => 1.0,
+            _ => return Err(invalid("unknown result")),
+        };
+
+        let board = BoardState::from_parts(pieces, color_on_move, CastlingRight::from_raw(flags & Self::CASTLING_MASK),
+                                           en_passant_position, bytes[26] as u16, u16::from_le_bytes([bytes[27], bytes[28]]));
+        return Ok(TrainingRecord {
+            board,
+            score: i16::from_le_bytes([bytes[29], bytes[30]]),
+            result,
+        });
+    }
+
+    /// Header of a game: empty occupancy, which no position has, game number and count of its records.
+    fn header(game: u64, records: usize) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[8..16].copy_from_slice(&game.to_le_bytes());
+        bytes[16..20].copy_from_slice(&(records as u32).to_le_bytes());
+        return bytes;
+    }
+
+    /// Game number and record count of a header, None for position records.
+    fn parse_header(bytes: &[u8]) -> Option<(u64, usize)> {
+        if bytes[0..8].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let game = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        return Some((game, u32::from_le_bytes(bytes[16..20].try_into().unwrap()) as usize));
+    }
+
+    fn read_bytes(path: &Path) -> Result<Vec<u8>, GameError> {
+        let mut bytes = Vec::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut bytes))
+            .map_err(|e| GameError::ConfigError(format!("cannot read {}: {}", path.display(), e)))?;
+        return Ok(bytes);
+    }
+
+    /// Reads all position records of a file, game headers are skipped.
+    pub fn read(path: &Path) -> Result<Vec<TrainingRecord>, GameError> {
+        return Self::read_bytes(path)?.chunks_exact(Self::SIZE)
+            .filter(|chunk| Self::parse_header(chunk).is_none())
+            .map(Self::from_bytes)
+            .collect();
+    }
+
+    /// Appends a game with its header to a file, creating it when it does not exist.
+    /// The header is written even for games without records so they count as finished.
+    pub fn append_game(path: &Path, game: u64, records: &[TrainingRecord]) -> Result<(), GameError> {
+        let error = |e: std::io::Error| GameError::ConfigError(format!("cannot write {}: {}", path.display(), e));
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(error)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&Self::header(game, records.len())).map_err(error)?;
+        for record in records {
+            writer.write_all(&record.to_bytes()).map_err(error)?;
+        }
+        return writer.flush().map_err(error);
+    }
+
+    /// Prepares a file for an interrupted run to continue and returns numbers of the games stored
+    /// in it. Games are written in the order they finish, so the numbers can have gaps.
+    /// A game cut off by the interruption is dropped from the end of the file.
+    pub fn resume(path: &Path) -> Result<HashSet<u64>, GameError> {
+        let mut finished = HashSet::new();
+        if !path.exists() {
+            return Ok(finished);
+        }
+
+        let bytes = Self::read_bytes(path)?;
+        let chunks: Vec<&[u8]> = bytes.chunks_exact(Self::SIZE).collect();
+        let mut index = 0;
+        while index < chunks.len() {
+            let (game, records) = Self::parse_header(chunks[index])
+                .ok_or_else(|| GameError::ConfigError(format!("{} has a record outside of a game", path.display())))?;
+            if index + 1 + records > chunks.len() {
+                break;
+            }
+            finished.insert(game);
+            index += 1 + records;
+        }
+
+        let complete = (index * Self::SIZE) as u64;
+        if bytes.len() as u64 != complete {
+            let file = OpenOptions::new().write(true).open(path)
+                .map_err(|e| GameError::ConfigError(format!("cannot open {}: {}", path.display(), e)))?;
+            file.set_len(complete).map_err(|e| GameError::ConfigError(format!("cannot truncate {}: {}", path.display(), e)))?;
+        }
+        return Ok(finished);
+    }
+}
+
+impl From<TrainingRecord> for TuningPosition {
+    fn from(record: TrainingRecord) -> Self {
+        return TuningPosition { board: record.board, result: record.result };
+    }
+}
+
+/// Settings of self-play games used to generate training data.
+#[derive(Debug, Clone, Copy)]
+pub struct SelfPlayConfig {
+    pub nodes: u64,
+    // Random moves played from the starting position before the engine takes over
+    pub random_plies: usize,
+    // Games that are not finished after this many plies are drawn
+    pub max_plies: usize,
+    // Game is won once the score stays above this for a few moves of both sides
+    pub win_score: i32,
+}
+
+impl SelfPlayConfig {
+    pub const DEFAULT: Self = Self { nodes: 5_000, random_plies: 8, max_plies: 400, win_score: 2_000 };
+}
+
+impl Default for SelfPlayConfig {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+/// Plays games of the engine against itself from randomized openings and records quiet positions.
+pub struct SelfPlay {
+    engine: Minimax,
+    config: SelfPlayConfig,
+}
+
+impl SelfPlay {
+    const WIN_PLIES: usize = 4;
+    const MAX_OPENING_ATTEMPTS: usize = 100;
+
+    pub fn new(engine: Minimax, config: SelfPlayConfig) -> Self {
+        return Self { engine, config };
+    }
+
+    /// Plays one game, the same seed always gives the same opening.
+    pub fn play_game(&mut self, seed: u64) -> Vec<TrainingRecord> {
+        let (mut board, mut history) = Self::random_opening(seed, self.config.random_plies);
+        let mut records: Vec<TrainingRecord> = Vec::new();
+        let mut winning_plies = 0;
+        let mut result = 0.5;
+
+        for _ in 0..self.config.max_plies {
+            let moves = MoveProvider::INSTANCE.legal_moves(&board);
+            if moves.is_empty() {
+                if MoveProvider::INSTANCE.is_in_check(&board) {
+                    result = if board.on_move() == Color::White { 0.0 } else { 1.0 };
+                }
+                break;
+            }
+            let key = board.zobrist_key();
+            if board.half_move_clock() >= 100 || history.iter().filter(|k| **k == key).count() >= 2 || Self::is_insufficient_material(&board) {
+                break;
+            }
+
+            self.engine.set_history(history.clone());
+            let limits = SearchLimits { nodes: Some(self.config.nodes), ..SearchLimits::default() };
+            let search = match self.engine.search_with_limits(&board, &limits) {
+                Ok(search) => search,
+                Err(_) => break,
+            };
+
+            let white = if board.on_move() == Color::White { 1 } else { -1 };
+            let score = match search.score {
+                Score::Centipawns(cp) => Some(cp * white),
+                Score::Mate(_) => None,
+            };
+            // Positions are kept quiet, the score of tactical ones is not stable enough for training
+            let quiet = !MoveProvider::INSTANCE.is_in_check(&board)
+                && !board.is_capture(search.best_move) && !board.is_promotion(search.best_move);
+            if let (Some(score), true) = (score, quiet) {
+                records.push(TrainingRecord {
+                    board,
+                    score: score.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+                    result: 0.5,
+                });
+            }
+
+            let advantage = match search.score {
+                Score::Centipawns(cp) => cp * white,
+                Score::Mate(moves) => moves.signum() * white * self.config.win_score,
+            };
+            winning_plies = if advantage.abs() >= self.config.win_score { winning_plies + 1 } else { 0 };
+            if winning_plies >= Self::WIN_PLIES {
+                result = if advantage > 0 { 1.0 } else { 0.0 };
+                break;
+            }
+
+            history.push(key);
+            board = board.make_move(search.best_move);
+        }
+
+        for record in records.iter_mut() {
+            record.result = result;
+        }
+        return records;
+    }
+
+    /// Plays random legal moves from the starting position, openings ending the game are replaced.
+    fn random_opening(seed: u64, plies: usize) -> (BoardState, Vec<u64>) {
+        // Seeds of consecutive games are spread over all bits before they drive the generator
+        let mut random = SkillLevel::next_random(seed.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        for _ in 0..Self::MAX_OPENING_ATTEMPTS {
+            let mut board = BoardState::default();
+            let mut history = Vec::with_capacity(plies);
+            for _ in 0..plies {
+                let moves = MoveProvider::INSTANCE.legal_moves(&board);
+                if moves.is_empty() {
+                    break;
+                }
+                random = SkillLevel::next_random(random);
+                history.push(board.zobrist_key());
+                board = board.make_move(moves[(random % moves.len() as u64) as usize]);
+            }
+            if !MoveProvider::INSTANCE.legal_moves(&board).is_empty() {
+                return (board, history);
+            }
+        }
+        return (BoardState::default(), Vec::new());
+    }
+
+    /// Only kings, or kings with a single minor piece, are left.
+    fn is_insufficient_material(board: &BoardState) -> bool {
+        let minors = [Piece::Bishop, Piece::Knight].iter()
+            .map(|p| (board.pieces[0][p.index()] | board.pieces[1][p.index()]).bit_count())
+            .sum::<u32>();
+        return board.all_pieces().bit_count() - 2 == minors && minors <= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::ai::evaluator::Evaluator;
+
+    #[test]
+    fn test_record_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 12 40",
+            "8/8/4k3/8/8/8/2K5/8 w - - 99 120",
+        ];
+        for (i, fen) in fens.iter().enumerate() {
+            let record = TrainingRecord {
+                board: BoardState::from_fen(fen).unwrap(),
+                score: -345,
+                result: i as f64 / 2.0,
+            };
+            let decoded = TrainingRecord::from_bytes(&record.to_bytes()).unwrap();
+            assert_eq!(*fen, decoded.board.to_fen());
+            assert_eq!(record.score, decoded.score);
+            assert_eq!(record.result, decoded.result);
+        }
+        assert!(TrainingRecord::from_bytes(&[0; 10]).is_err());
+        assert!(TrainingRecord::from_bytes(&TrainingRecord::header(3, 1)).is_err());
+    }
+
+    #[test]
+    fn test_resume_skips_finished_games() {
+        let record = TrainingRecord { board: BoardState::default(), score: 20, result: 0.5 };
+        let path = std::env::temp_dir().join("chess_rot_training_data_resume_test.bin");
+        let _ = std::fs::remove_file(&path);
+        assert!(TrainingRecord::resume(&path).unwrap().is_empty());
+
+        // Games finish out of order, game 5 has no quiet positions
+        TrainingRecord::append_game(&path, 2, &[record, record]).unwrap();
+        TrainingRecord::append_game(&path, 0, &[record]).unwrap();
+        TrainingRecord::append_game(&path, 5, &[]).unwrap();
+        // Game 1 is interrupted after its header and one of its three records
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&TrainingRecord::header(1, 3)).unwrap();
+        file.write_all(&record.to_bytes()).unwrap();
+        file.write_all(&[1, 2, 3]).unwrap();
+        drop(file);
+
+        assert_eq!(HashSet::from([0, 2, 5]), TrainingRecord::resume(&path).unwrap());
+        assert_eq!(3, TrainingRecord::read(&path).unwrap().len());
+        assert_eq!(6 * TrainingRecord::SIZE as u64, std::fs::metadata(&path).unwrap().len());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_self_play_records_game() {
+        let config = SelfPlayConfig { nodes: 300, max_plies: 20, ..SelfPlayConfig::DEFAULT };
+        let mut self_play = SelfPlay::new(Minimax::new(Evaluator::new(), 64, 0.0), config);
+        let records = self_play.play_game(7);
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|r| r.result == records[0].result));
+
+        let path = std::env::temp_dir().join("chess_rot_training_data_test.bin");
+        let _ = std::fs::remove_file(&path);
+        TrainingRecord::append_game(&path, 7, &records).unwrap();
+        assert_eq!(HashSet::from([7]), TrainingRecord::resume(&path).unwrap());
+        assert_eq!(records.len(), TrainingRecord::read(&path).unwrap().len());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::chess::Move;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+impl Bound {
+    // Zero is never used, so stored data can not be mistaken for an empty slot
+    fn to_u64(self) -> u64 {
+        return match self {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+    }
+
+    fn from_u64(value: u64) -> Bound {
+        return match value {
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => Bound::Exact,
+        };
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct TranspositionEntry {
+    pub key: u64,
+    pub best_move: Option<Move>,
+    pub depth: usize,
+    pub score: i32,
+    pub bound: Bound,
+}
+
+impl TranspositionEntry {
+    const MOVE_MASK: u64 = (1 << 22) - 1;
+    const HAS_MOVE_BIT: u64 = 1 << 22;
+    const DEPTH_OFFSET: u64 = 23;
+    const DEPTH_MASK: u64 = 0b1111111;
+    const BOUND_OFFSET: u64 = 30;
+    const BOUND_MASK: u64 = 0b11;
+    const SCORE_OFFSET: u64 = 32;
+
+    fn pack(&self) -> u64 {
+        let m = self.best_move.map_or(0, |m| (m.raw() & Self::MOVE_MASK) | Self::HAS_MOVE_BIT);
+        return m
+            | ((self.depth as u64).min(Self::DEPTH_MASK) << Self::DEPTH_OFFSET)
+            | (self.bound.to_u64() << Self::BOUND_OFFSET)
+            | ((self.score as u32 as u64) << Self::SCORE_OFFSET);
+    }
+
+    fn unpack(key: u64, data: u64) -> TranspositionEntry {
+        let best_move = if data & Self::HAS_MOVE_BIT != 0 {
+            Some(Move::from_raw(data & Self::MOVE_MASK))
+        } else {
+            None
+        };
+
+        return TranspositionEntry {
+            key,
+            best_move,
+            depth: ((data >> Self::DEPTH_OFFSET) & Self::DEPTH_MASK) as usize,
+            score: (data >> Self::SCORE_OFFSET) as u32 as i32,
+            bound: Bound::from_u64((data >> Self::BOUND_OFFSET) & Self::BOUND_MASK),
+        };
+    }
+}
+
+// Key is stored xored with data, torn writes from other threads then fail the key check
+#[derive(Debug, Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Fixed size table of searched positions indexed by zobrist key.
+/// Lock-free, so it can be shared by search threads.
+/// Reference: https://www.chessprogramming.org/Shared_Hash_Table#Lockless
+#[derive(Debug)]
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_SIZE_MB: usize = 16;
+
+    pub fn new(size_mb: usize) -> Self {
+        let capacity = (size_mb * 1024 * 1024 / std::mem::size_of::<Slot>()).max(1);
+        return Self { slots: (0..capacity).map(|_| Slot::default()).collect() };
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TranspositionEntry> {
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        return Some(TranspositionEntry::unpack(key, data));
+    }
+
+    /// Stores entry, replacing the old one unless it is a deeper search of the same position.
+    pub fn store(&self, key: u64, best_move: Option<Move>, depth: usize, score: i32, bound: Bound) {
+        let existing = self.probe(key);
+        if let Some(existing) = existing {
+            if existing.depth > depth && bound != Bound::Exact {
+                return;
+            }
+        }
+
+        let best_move = best_move.or(existing.and_then(|e| e.best_move));
+        let data = TranspositionEntry { key, best_move, depth, score, bound }.pack();
+        let slot = &self.slots[self.index(key)];
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    /// Permille of used entries, estimated from the beginning of the table.
+    pub fn hashfull(&self) -> usize {
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample].iter().filter(|s| s.data.load(Ordering::Relaxed) != 0).count();
+        return used * 1000 / sample;
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        return (key % self.slots.len() as u64) as usize;
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        return Self::new(Self::DEFAULT_SIZE_MB);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_probe() {
+        let table = TranspositionTable::new(1);
+        let m = Move::from_to_target(12, 28, crate::chess::Piece::None);
+        table.store(42, Some(m), 5, -99_950, Bound::Lower);
+
+        let entry = table.probe(42).unwrap();
+        assert_eq!(Some(m), entry.best_move);
+        assert_eq!(5, entry.depth);
+        assert_eq!(-99_950, entry.score);
+        assert_eq!(Bound::Lower, entry.bound);
+        assert_eq!(None, table.probe(43));
+
+        // shallower bound keeps deeper entry, exact score replaces it and keeps the move
+        table.store(42, None, 3, 10, Bound::Upper);
+        assert_eq!(5, table.probe(42).unwrap().depth);
+        table.store(42, None, 3, 10, Bound::Exact);
+        assert_eq!(Some(m), table.probe(42).unwrap().best_move);
+        assert_eq!(10, table.probe(42).unwrap().score);
+    }
+}
@@ -0,0 +1,189 @@
+use std::thread;
+use crate::chess::{BoardState, GameError};
+use crate::chess::ai::evaluate::Evaluate;
+use crate::chess::ai::evaluation_parameters::EvaluationParameters;
+use crate::chess::ai::evaluator::Evaluator;
+
+/// Quiet position labelled with result of the game it comes from, 1.0 is white win, 0.5 draw and 0.0 black win.
+#[derive(Debug, Clone)]
+pub struct TuningPosition {
+    pub board: BoardState,
+    pub result: f64,
+}
+
+impl TuningPosition {
+    /// Parses FEN followed by the result, e.g. `<fen> [1-0]`, `<fen> 0.5` or `<fen> c9 "0-1";`.
+    /// Move counters may be left out.
+    pub fn from_line(line: &str) -> Result<TuningPosition, GameError> {
+        let mut fields: Vec<&str> = line.split_whitespace().collect();
+        let result = fields.pop().map(|r| r.trim_matches(|c| c == '[' || c == ']' || c == '"' || c == ';'));
+        let result = match result {
+            Some("1-0") | Some("1.0") | Some("1") => 1.0,
+            Some("1/2-1/2") | Some("0.5") => 0.5,
+            Some("0-1") | Some("0.0") | Some("0") => 0.0,
+            _ => return Err(GameError::FenFormatError(format!("Missing game result: {}", line))),
+        };
+        if fields.last() == Some(&"c9") {
+            fields.pop();
+        }
+
+        let fen = match fields.len() {
+            4 => format!("{} 0 1", fields.join(" ")),
+            6 => fields.join(" "),
+            _ => return Err(GameError::FenFormatError(format!("Invalid position: {}", line))),
+        };
+        return Ok(TuningPosition { board: BoardState::from_fen(&fen)?, result });
+    }
+
+    pub fn parse(str: &str) -> Result<Vec<TuningPosition>, GameError> {
+        return str.lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .map(Self::from_line)
+            .collect();
+    }
+}
+
+/// Texel tuning, local search that changes one value at a time while the mean squared error
+/// between game results and the evaluation mapped to expected score decreases.
+/// Reference: https://www.chessprogramming.org/Texel%27s_Tuning_Method
+pub struct Tuner {
+    positions: Vec<TuningPosition>,
+    threads: usize,
+    // Scaling of the evaluation in the sigmoid, fitted to the positions before tuning
+    k: f64,
+}
+
+impl Tuner {
+    const K_ITERATIONS: usize = 10;
+    const PAWN_TABLE_ENTRIES: usize = 256;
+
+    pub fn new(positions: Vec<TuningPosition>, threads: usize) -> Self {
+        return Self { positions, threads: threads.max(1), k: 1.0 };
+    }
+
+    pub fn k(&self) -> f64 {
+        return self.k;
+    }
+
+    /// Expected score of white for evaluation in centipawns.
+    pub fn sigmoid(k: f64, evaluation: i32) -> f64 {
+        return 1.0 / (1.0 + 10f64.powf(-k * evaluation as f64 / 400.0));
+    }
+
+    /// Mean squared error of the evaluation with the parameters over all positions.
+    pub fn error(&self, params: &EvaluationParameters) -> f64 {
+        if self.positions.is_empty() {
+            return 0.0;
+        }
+
+        let chunk_size = self.positions.len().div_ceil(self.threads);
+        let sum: f64 = thread::scope(|scope| {
+            let handles: Vec<_> = self.positions.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || {
+                    // Evaluator lives for a single pass over the positions, a large pawn table would not pay off
+                    let evaluator = Evaluator::with_pawn_table(params.clone(), Self::PAWN_TABLE_ENTRIES);
+                    return chunk.iter()
+                        .map(|p| (p.result - Self::sigmoid(self.k, evaluator.evaluate(&p.board))).powi(2))
+                        .sum::<f64>();
+                }))
+                .collect();
+            return handles.into_iter().map(|h| h.join().unwrap()).sum();
+        });
+        return sum / self.positions.len() as f64;
+    }
+
+    /// Finds the sigmoid scaling with the lowest error by refining the step around the best value.
+    pub fn fit_k(&mut self, params: &EvaluationParameters) -> f64 {
+        let mut best = (self.k, self.error(params));
+        let mut step = 0.5;
+        for _ in 0..Self::K_ITERATIONS {
+            let center = best.0;
+            for k in [center - step, center + step] {
+                if k <= 0.0 {
+                    continue;
+                }
+                self.k = k;
+                let error = self.error(params);
+                if error < best.1 {
+                    best = (k, error);
+                }
+            }
+            step /= 2.0;
+        }
+        self.k = best.0;
+        return self.k;
+    }
+
+    /// Runs epochs of local search until no value improves the error or the epoch limit is hit.
+    /// After every epoch the callback gets its number, the error and the parameters found so far.
+    pub fn tune<F>(&self, params: &EvaluationParameters, epochs: usize, mut on_epoch: F) -> EvaluationParameters
+    where
+        F: FnMut(usize, f64, &EvaluationParameters),
+    {
+        let mut best = params.clone();
+        let mut values = best.values();
+        let mut best_error = self.error(&best);
+        for epoch in 1..=epochs {
+            let mut improved = false;
+            for i in 0..values.len() {
+                for delta in [1, -1] {
+                    values[i] += delta;
+                    let mut candidate = best.clone();
+                    candidate.set_values(&values);
+                    let error = self.error(&candidate);
+                    if error < best_error {
+                        best = candidate;
+                        best_error = error;
+                        improved = true;
+                        break;
+                    }
+                    values[i] -= delta;
+                }
+            }
+
+            on_epoch(epoch, best_error, &best);
+            if !improved {
+                break;
+            }
+        }
+        return best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_positions() {
+        let positions = TuningPosition::parse(
+            "# comment\n\
+             rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [1/2-1/2]\n\
+             4k3/8/8/8/8/8/8/3QK3 w - - 1-0\n\
+             4k3/8/8/8/8/8/8/3QK3 b - - c9 \"0-1\";\n").unwrap();
+        let results: Vec<f64> = positions.iter().map(|p| p.result).collect();
+        assert_eq!(vec![0.5, 1.0, 0.0], results);
+        assert!(TuningPosition::from_line("4k3/8/8/8/8/8/8/3QK3 w - -").is_err());
+        assert!(TuningPosition::from_line("4k3/8/8/8/8/8/8/3QK3 w - 1-0").is_err());
+    }
+
+    #[test]
+    fn test_tuning_reduces_error() {
+        // Knights are worth more than bishops in these games
+        let positions = TuningPosition::parse(
+            "4k3/pppp4/8/8/8/8/PPPP4/1N2K3 w - - 1-0\n\
+             1n2k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0-1\n\
+             2b1k3/pppp4/8/8/8/8/PPPP4/1N2K3 w - - 1-0\n\
+             1n2k3/pppp4/8/8/8/8/PPPP4/2B1K3 w - - 0-1\n").unwrap();
+        let mut tuner = Tuner::new(positions, 1);
+        let params = EvaluationParameters::default();
+        tuner.fit_k(&params);
+        let initial = tuner.error(&params);
+
+        let mut errors = Vec::new();
+        let tuned = tuner.tune(&params, 1, |_, error, _| errors.push(error));
+        assert_eq!(1, errors.len());
+        assert!(errors[0] < initial);
+        assert_eq!(errors[0], tuner.error(&tuned));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::bitboard::BitBoard;
 use crate::chess::game::Game;
-use crate::chess::{CastlingRight, Color, GameError, Move, MoveType, Piece, Square, SquareLabel};
+use crate::chess::{CastlingRight, Color, GameError, Move, MoveType, Piece, Square, SquareLabel, Zobrist};
+use crate::chess::move_provider::MoveProvider;
 use std::error::Error;
 use std::fmt;
 use std::ops::{BitAndAssign, BitOrAssign};
@@ -107,11 +108,14 @@ impl BoardState {
         let mut castling = CastlingRight::default();
         if !fen_castling.contains("K") {
             castling = castling.remove_king_side_castle(Color::White)
-        } else if !fen_castling.contains("Q") {
+        }
+        if !fen_castling.contains("Q") {
             castling = castling.remove_queen_side_castle(Color::White)
-        } else if !fen_castling.contains("k") {
+        }
+        if !fen_castling.contains("k") {
             castling = castling.remove_king_side_castle(Color::Black)
-        } else if !fen_castling.contains("q") {
+        }
+        if !fen_castling.contains("q") {
             castling = castling.remove_queen_side_castle(Color::Black)
         }
 
@@ -147,6 +151,28 @@ impl BoardState {
         });
     }
 
+    /// Builds position from piece bitboards, used when reading positions stored in binary formats.
+    pub fn from_parts(pieces: [[BitBoard; 6]; 2], color_on_move: Color, castling: CastlingRight,
+                      en_passant_position: Option<Square>, half_move_clock: u16, full_moves: u16) -> BoardState {
+        let mut pieces_for_color = [BitBoard::empty(), BitBoard::empty()];
+        for color in [Color::White, Color::Black] {
+            for bitboard in pieces[color.index()] {
+                pieces_for_color[color.index()] |= bitboard;
+            }
+        }
+
+        return BoardState {
+            pieces_for_color,
+            pieces,
+            half_move_clock,
+            ply: full_moves * 2 + if color_on_move == Color::White { 0 } else { 1 },
+            score: 0,
+            color_on_move,
+            castling,
+            en_passant_position,
+        };
+    }
+
     pub fn get_piece_at(&self, sqr: u64) -> Option<(Piece, Color)> {
         return if self.pieces_for_color[Color::White.index()].is_bit_set(sqr) {
             self.find_piece_at_square_for_color(Color::White, sqr)
@@ -289,7 +315,7 @@ impl BoardState {
                         // checks whether its king or queen side castle
                         if m.get_from() == Square::E8 && m.get_to() == Square::C8 {
                             SquareLabel::A8.to_bb() | SquareLabel::D8.to_bb()
-                        } else if m.get_from() == Square::E1 && m.get_to() == Square::G8 {
+                        } else if m.get_from() == Square::E8 && m.get_to() == Square::G8 {
                             SquareLabel::H8.to_bb() | SquareLabel::F8.to_bb()
                         } else {
                             BitBoard::empty()
@@ -303,26 +329,29 @@ impl BoardState {
                 }
                 castling = self.castling.remove_both_side_castle(on_move);
             }
-            Piece::Rook => match on_move {
-                Color::White => {
-                    if m.get_from().raw() == SquareLabel::A1.as_u64() {
-                        castling = self.castling.remove_king_side_castle(Color::White);
-                    } else if m.get_from().raw() == SquareLabel::A8.as_u64() {
-                        castling = self.castling.remove_queen_side_castle(Color::White);
-                    }
-                }
-                Color::Black => {
-                    if m.get_from().raw() == SquareLabel::H1.as_u64() {
-                        castling = self.castling.remove_king_side_castle(Color::Black);
-                    } else if (m.get_from().raw() == SquareLabel::H8.as_u64()) {
-                        castling = self.castling.remove_queen_side_castle(Color::Black);
-                    }
+            Piece::Pawn => {
+                half_move_clock = 0;
+                if self.is_promotion(m) {
+                    pieces[on_move.index()][Piece::Pawn.index()] ^= to_bb;
+                    pieces[on_move.index()][m.get_target_piece().index()] |= to_bb;
                 }
-            },
-            Piece::Pawn => half_move_clock = 0,
+            }
             _ => {}
         }
 
+        // Rook leaving or captured on its starting square loses the castling right
+        for square in [m.get_from(), m.get_to()] {
+            if square == Square::A1 {
+                castling = castling.remove_queen_side_castle(Color::White);
+            } else if square == Square::H1 {
+                castling = castling.remove_king_side_castle(Color::White);
+            } else if square == Square::A8 {
+                castling = castling.remove_queen_side_castle(Color::Black);
+            } else if square == Square::H8 {
+                castling = castling.remove_king_side_castle(Color::Black);
+            }
+        }
+
         return BoardState {
             pieces,
             pieces_for_color,
@@ -335,6 +364,43 @@ impl BoardState {
         };
     }
 
+    /// Passes the turn to the opponent, used by null-move pruning.
+    pub fn make_null_move(&self) -> BoardState {
+        return BoardState {
+            color_on_move: self.color_on_move.inverse(),
+            en_passant_position: None,
+            half_move_clock: self.half_move_clock + 1,
+            ply: self.ply + 1,
+            ..*self
+        };
+    }
+
+    /// Whether color has any piece other than pawns and king.
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        let pawns_and_king = self.pieces[color.index()][Piece::Pawn.index()] | self.pieces[color.index()][Piece::King.index()];
+        return !(self.pieces_for_color[color.index()] & !pawns_and_king).is_empty();
+    }
+
+    /// Returns piece of the opposite color that is taken by the move.
+    pub fn captured_piece(&self, m: Move) -> Option<Piece> {
+        return self.get_piece_at(m.get_to().raw())
+            .filter(|(_, c)| *c != self.color_on_move)
+            .map(|(p, _)| p);
+    }
+
+    pub fn is_capture(&self, m: Move) -> bool {
+        return self.captured_piece(m).is_some();
+    }
+
+    pub fn is_promotion(&self, m: Move) -> bool {
+        let promoted = m.get_target_piece();
+        return (m.get_to().rank() == 0 || m.get_to().rank() == 7)
+            && promoted != Piece::None
+            && promoted != Piece::King
+            && promoted != Piece::Pawn
+            && self.pieces[self.color_on_move.index()][Piece::Pawn.index()].is_bit_set(m.get_from().raw());
+    }
+
     pub fn remove_piece(&mut self, sqr: u64) {
         let removed = self.get_piece_at(sqr);
         if removed.is_some() {
@@ -345,6 +411,14 @@ impl BoardState {
         }
     }
 
+    pub fn zobrist_key(&self) -> u64 {
+        return Zobrist::INSTANCE.key(self);
+    }
+
+    pub fn pawn_key(&self) -> u64 {
+        return Zobrist::INSTANCE.pawn_key(self);
+    }
+
     pub fn full_moves(&self) -> u16 {
         return self.ply / 2;
     }
@@ -353,6 +427,10 @@ impl BoardState {
         return self.ply;
     }
 
+    pub fn half_move_clock(&self) -> u16 {
+        return self.half_move_clock;
+    }
+
     pub fn on_move(&self) -> Color {
         return self.color_on_move;
     }
@@ -508,6 +586,67 @@ impl BoardState {
 
         return str;
     }
+
+    // Reference: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn to_san(&self, m: Move) -> String {
+        let mut str = String::new();
+        let (piece, _) = match self.get_piece_at(m.get_from().raw()) {
+            Some(p) => p,
+            None => return str,
+        };
+
+        let from = m.get_from();
+        let to = m.get_to();
+        if piece == Piece::King && from.file().abs_diff(to.file()) == 2 {
+            str.push_str(if to.file() == 6 { "O-O" } else { "O-O-O" });
+        } else {
+            let capture = self.is_capture(m) || (piece == Piece::Pawn && from.file() != to.file());
+            if piece == Piece::Pawn {
+                if capture {
+                    str.push((b'a' + from.file() as u8) as char);
+                }
+            } else {
+                str.push(piece.to_char_representation(Color::White));
+
+                let ambiguous: Vec<Move> = MoveProvider::INSTANCE.legal_moves(self)
+                    .into_iter()
+                    .filter(|o| o.get_to() == to && o.get_from() != from)
+                    .filter(|o| self.get_piece_at(o.get_from().raw()).map(|(p, _)| p) == Some(piece))
+                    .collect();
+                if !ambiguous.is_empty() {
+                    let algebraic = from.to_algebraic();
+                    if ambiguous.iter().all(|o| o.get_from().file() != from.file()) {
+                        str.push_str(&algebraic[..1]);
+                    } else if ambiguous.iter().all(|o| o.get_from().rank() != from.rank()) {
+                        str.push_str(&algebraic[1..]);
+                    } else {
+                        str.push_str(&algebraic);
+                    }
+                }
+            }
+
+            if capture {
+                str.push('x');
+            }
+            str.push_str(&to.to_algebraic());
+
+            if self.is_promotion(m) {
+                str.push('=');
+                str.push(m.get_target_piece().to_char_representation(Color::White));
+            }
+        }
+
+        let next_state = self.make_move(m);
+        if MoveProvider::INSTANCE.is_in_check(&next_state) {
+            if MoveProvider::INSTANCE.legal_moves(&next_state).is_empty() {
+                str.push('#');
+            } else {
+                str.push('+');
+            }
+        }
+
+        return str;
+    }
 }
 
 impl Default for BoardState {
@@ -25,6 +25,10 @@ impl CastlingRight {
         return CastlingRight { value };
     }
 
+    pub fn raw(&self) -> u8 {
+        return self.value;
+    }
+
     pub fn is_white_king_side_allowed(&self) -> bool {
         return self.value & CastlingRight::WHITE_KING_SIDE_MASK != 0;
     }
@@ -43,22 +47,22 @@ impl CastlingRight {
 
     pub fn remove_king_side_castle(&self, color: Color) -> CastlingRight {
         return match color {
-            Color::White => CastlingRight::from_raw(self.value ^ CastlingRight::WHITE_KING_SIDE_MASK),
-            Color::Black => CastlingRight::from_raw(self.value ^ CastlingRight::BLACK_KING_SIDE_MASK),
+            Color::White => CastlingRight::from_raw(self.value & !CastlingRight::WHITE_KING_SIDE_MASK),
+            Color::Black => CastlingRight::from_raw(self.value & !CastlingRight::BLACK_KING_SIDE_MASK),
         };
     }
 
     pub fn remove_queen_side_castle(&self, color: Color) -> CastlingRight {
         return match color {
-            Color::White => CastlingRight::from_raw(self.value ^ CastlingRight::WHITE_QUEEN_SIDE_MASK),
-            Color::Black => CastlingRight::from_raw(self.value ^ CastlingRight::BLACK_QUEEN_SIDE_MARK),
+            Color::White => CastlingRight::from_raw(self.value & !CastlingRight::WHITE_QUEEN_SIDE_MASK),
+            Color::Black => CastlingRight::from_raw(self.value & !CastlingRight::BLACK_QUEEN_SIDE_MARK),
         };
     }
 
     pub fn remove_both_side_castle(&self, color: Color) -> CastlingRight {
         return match color {
-            Color::White => CastlingRight::from_raw(self.value ^ (CastlingRight::WHITE_QUEEN_SIDE_MASK | CastlingRight::WHITE_KING_SIDE_MASK)),
-            Color::Black => CastlingRight::from_raw(self.value ^ (CastlingRight::BLACK_QUEEN_SIDE_MARK | CastlingRight::BLACK_KING_SIDE_MASK)),
+            Color::White => CastlingRight::from_raw(self.value & !(CastlingRight::WHITE_QUEEN_SIDE_MASK | CastlingRight::WHITE_KING_SIDE_MASK)),
+            Color::Black => CastlingRight::from_raw(self.value & !(CastlingRight::BLACK_QUEEN_SIDE_MARK | CastlingRight::BLACK_KING_SIDE_MASK)),
         };
     }
 
@@ -0,0 +1,58 @@
+use crate::chess::{BoardState, GameError, Move};
+
+/// Single record of Extended Position Description, used for test suites.
+/// Reference: https://www.chessprogramming.org/Extended_Position_Description
+#[derive(Debug, Clone)]
+pub struct Epd {
+    pub board_state: BoardState,
+    pub id: String,
+    pub best_moves: Vec<String>,
+    pub avoid_moves: Vec<String>,
+}
+
+impl Epd {
+    pub fn from_line(line: &str) -> Result<Epd, GameError> {
+        let fields: Vec<&str> = line.trim().splitn(5, ' ').collect();
+        if fields.len() < 4 {
+            return Err(GameError::FenFormatError(format!("Invalid EPD format: {}", line)));
+        }
+
+        let fen = format!("{} {} {} {} 0 1", fields[0], fields[1], fields[2], fields[3]);
+        let board_state = BoardState::from_fen(&fen)?;
+
+        let mut id = String::new();
+        let mut best_moves = Vec::new();
+        let mut avoid_moves = Vec::new();
+        let operations = fields.get(4).unwrap_or(&"");
+        for operation in operations.split(';') {
+            let operation = operation.trim();
+            let (opcode, operands) = operation.split_once(' ').unwrap_or((operation, ""));
+            match opcode {
+                "id" => id = operands.trim_matches('"').to_string(),
+                "bm" => best_moves = operands.split_whitespace().map(Self::strip_annotations).collect(),
+                "am" => avoid_moves = operands.split_whitespace().map(Self::strip_annotations).collect(),
+                _ => {}
+            }
+        }
+
+        return Ok(Epd { board_state, id, best_moves, avoid_moves });
+    }
+
+    pub fn parse(str: &str) -> Result<Vec<Epd>, GameError> {
+        return str.lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .map(Self::from_line)
+            .collect();
+    }
+
+    /// Checks whether the move satisfies both best move and avoid move operations.
+    pub fn is_solved_by(&self, m: Move) -> bool {
+        let san = Self::strip_annotations(&self.board_state.to_san(m));
+        return (self.best_moves.is_empty() || self.best_moves.contains(&san))
+            && !self.avoid_moves.contains(&san);
+    }
+
+    fn strip_annotations(san: &str) -> String {
+        return san.trim_end_matches(|c| c == '+' || c == '#' || c == '!' || c == '?').to_string();
+    }
+}
@@ -9,6 +9,7 @@ pub enum GameError {
     NoPossibleMoveError,
     InvalidSquareError(String),
     InvalidMoveError,
+    ConfigError(String),
 }
 
 impl fmt::Display for GameError {
@@ -29,6 +30,9 @@ impl fmt::Display for GameError {
             GameError::InvalidMoveError => {
                 write!(f, "Invalid move")
             }
+            GameError::ConfigError(err) => {
+                write!(f, "Invalid configuration: {}", err)
+            }
         }
     }
 }
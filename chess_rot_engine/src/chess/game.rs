@@ -83,6 +83,11 @@ impl Game {
         return None;
     }
 
+    /// Zobrist keys of positions before the current one, oldest first.
+    pub fn position_keys(&self) -> Vec<u64> {
+        return self.move_history.iter().map(|(state, _)| state.zobrist_key()).collect();
+    }
+
     // pub fn undo_last_move(&mut self) -> Result<(Move, BoardState), Err>{}
 }
 
@@ -7,6 +7,8 @@ mod game;
 mod piece;
 mod square;
 mod error;
+mod epd;
+mod zobrist;
 
 pub use self::castling::CastlingRight;
 pub use self::movement::chess_move::Move;
@@ -19,3 +21,5 @@ pub use self::piece::Piece;
 pub use self::piece::ColoredPiece;
 pub use self::square::{Square, SquareLabel};
 pub use self::game::{Game, GameResult};
+pub use self::epd::Epd;
+pub use self::zobrist::Zobrist;
@@ -75,6 +75,16 @@ impl Move {
         };
     }
 
+    pub const fn from_raw(value: u64) -> Self {
+        return Self {
+            bit_board: BitBoard::from(value)
+        };
+    }
+
+    pub const fn raw(self) -> u64 {
+        return self.bit_board.raw();
+    }
+
     pub fn get_type(self) -> MoveType {
         let value = self.bit_board.raw() & Self::MASK_3_BITS;
         return MoveType::try_from(value as usize).unwrap_or(MoveType::Invalid);
@@ -105,6 +115,17 @@ impl Move {
         return Piece::try_from(value).unwrap_or(Piece::None);
     }
 
+    /// Coordinate notation like e2e4 or e7e8q used by chess protocols.
+    pub fn to_uci(self) -> String {
+        let mut uci = format!("{}{}", self.get_from().to_algebraic(), self.get_to().to_algebraic());
+        let promotion = self.get_target_piece();
+        if matches!(self.get_type(), MoveType::Invalid | MoveType::Promotion)
+            && matches!(promotion, Piece::Queen | Piece::Rook | Piece::Bishop | Piece::Knight) {
+            uci.push(promotion.to_char());
+        }
+        return uci;
+    }
+
     pub fn to_capture(self, target_piece: Piece) -> Move {
         debug_assert_ne!(target_piece, Piece::None, "target piece cannot be none on capture");
         return Move::new(MoveType::Capture, self.get_from().raw(), self.get_to().raw(), self.get_piece(), self.get_color(), target_piece);
@@ -215,7 +215,7 @@ impl DiagonalMoveGenerator {
     }
 
     /// Generate attacks for one piece.
-    fn attacks(&self, i: Square, all_pieces: BitBoard) -> BitBoard {
+    pub fn attacks(&self, i: Square, all_pieces: BitBoard) -> BitBoard {
         // use magic multipliers to get occupancy state index
 
         let index_a1h8 =
@@ -440,7 +440,7 @@ impl LineMoveGenerator {
     }
 
     /// Generate attacks for one piece.
-    fn attacks(&self, i: Square, all_pieces: BitBoard) -> BitBoard {
+    pub fn attacks(&self, i: Square, all_pieces: BitBoard) -> BitBoard {
         // use magic multipliers to get occupancy state index
         let state_rank = (all_pieces & self.rank_mask[i.as_usize()]).raw() >> self.rank_shift[i.as_usize()];
         let state_file =
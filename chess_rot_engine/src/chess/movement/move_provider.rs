@@ -1,5 +1,5 @@
 use crate::bitboard::BitBoard;
-use crate::chess::{BoardState, Color, ColoredPiece, Move, MoveType, Piece};
+use crate::chess::{BoardState, Color, ColoredPiece, Move, MoveType, Piece, Square};
 use std::cmp::PartialEq;
 use crate::chess::movement::move_generator::{DiagonalMoveGenerator, PawnMoveGenerator, LineMoveGenerator, KnightJumpMoveGenerator, MoveGenerator, KingMoveGenerator};
 
@@ -33,6 +33,23 @@ impl MoveProvider {
             | self.king_move_generator.generate_attacks(board_state, color);
     }
 
+    /// Squares attacked by the piece of the color standing on the square, sliders stop at occupied squares.
+    pub fn piece_attacks(&self, piece: Piece, color: Color, square: Square, occupancy: BitBoard) -> BitBoard {
+        return match piece {
+            Piece::Pawn => self.pawn_move_generator.cached_attacks[color.index()][square.as_usize()],
+            Piece::Knight => self.knight_jump_move_generator.cached_attacks[square.as_usize()],
+            Piece::Bishop => self.diagonal_move_generator.attacks(square, occupancy),
+            Piece::Rook => self.line_move_generator.attacks(square, occupancy),
+            Piece::Queen => self.diagonal_move_generator.attacks(square, occupancy) | self.line_move_generator.attacks(square, occupancy),
+            Piece::King => self.king_move_generator.cached_attacks[square.as_usize()],
+            Piece::None => BitBoard::empty(),
+        };
+    }
+
+    pub fn pawn_attacks(&self, board_state: &BoardState, color: Color) -> BitBoard {
+        return self.pawn_move_generator.generate_attacks(board_state, color);
+    }
+
     pub fn for_each_move(&self, board: &BoardState, f: &mut impl FnMut(Move)) {
         self.line_move_generator.generate_moves(board, f);
         self.diagonal_move_generator.generate_moves(board, f);
@@ -53,11 +70,13 @@ impl MoveProvider {
     }
 
     pub fn is_king_under_attack(&self, board: &BoardState) -> bool {
-        let king_square = board.get_king(board.color_on_move.inverse()).lsb();
-        if (king_square == 0) {
+        let king = board.get_king(board.color_on_move.inverse());
+        if king.is_empty() {
             return false;
         }
 
+        let king_square = king.lsb();
+
         let all_pieces = board.all_pieces();
         let pieces_on_move = board.pieces[board.color_on_move.index()];
 
@@ -80,7 +99,7 @@ impl MoveProvider {
         }
 
         let file_mask = (all_pieces & self.line_move_generator.file_mask[king_square]).raw();
-        if !(self.line_move_generator.rank_attacks[king_square][(file_mask * self.line_move_generator.file_magic[king_square].raw()) as usize >> 57] & line_moving).is_empty() {
+        if !(self.line_move_generator.file_attacks[king_square][(file_mask * self.line_move_generator.file_magic[king_square].raw()) as usize >> 57] & line_moving).is_empty() {
             return true;
         }
 
@@ -98,6 +117,31 @@ impl MoveProvider {
         return false;
     }
 
+    /// Pieces of both colors attacking the square with given occupancy, used to resolve exchanges with x-rays.
+    pub fn attackers_to(&self, board: &BoardState, square: Square, occupancy: BitBoard) -> BitBoard {
+        let white = board.pieces[Color::White.index()];
+        let black = board.pieces[Color::Black.index()];
+        let pawns = (self.pawn_move_generator.cached_attacks[Color::White.index()][square.as_usize()] & black[Piece::Pawn.index()])
+            | (self.pawn_move_generator.cached_attacks[Color::Black.index()][square.as_usize()] & white[Piece::Pawn.index()]);
+        let knights = self.knight_jump_move_generator.cached_attacks[square.as_usize()]
+            & (white[Piece::Knight.index()] | black[Piece::Knight.index()]);
+        let kings = self.king_move_generator.cached_attacks[square.as_usize()]
+            & (white[Piece::King.index()] | black[Piece::King.index()]);
+        let queens = white[Piece::Queen.index()] | black[Piece::Queen.index()];
+        let lines = self.line_move_generator.attacks(square, occupancy)
+            & (white[Piece::Rook.index()] | black[Piece::Rook.index()] | queens);
+        let diagonals = self.diagonal_move_generator.attacks(square, occupancy)
+            & (white[Piece::Bishop.index()] | black[Piece::Bishop.index()] | queens);
+
+        return (pawns | knights | kings | lines | diagonals) & occupancy;
+    }
+
+    /// Checks whether king of the color currently on move is attacked.
+    pub fn is_in_check(&self, board: &BoardState) -> bool {
+        let color = board.color_on_move;
+        return self.is_under_attack(board, color.inverse(), board.get_king(color));
+    }
+
     pub fn is_under_attack(
         &self,
         board: &BoardState,
@@ -59,6 +59,13 @@ impl Square {
         return Some(Self::from_usize(square as usize));
     }
 
+    /// Square in algebraic notation, for example `e4`.
+    pub fn to_algebraic(&self) -> String {
+        let file = (b'a' + self.file() as u8) as char;
+        let rank = (b'1' + self.rank() as u8) as char;
+        return format!("{}{}", file, rank);
+    }
+
     pub const fn from_label(label: SquareLabel) -> Square {
         return Self::new(label as u64);
     }
@@ -0,0 +1,100 @@
+use crate::chess::{BoardState, Color, Piece};
+
+/// Random keys used to hash board positions.
+/// Reference: https://www.chessprogramming.org/Zobrist_Hashing
+pub struct Zobrist {
+    pieces: [[[u64; 64]; 6]; 2],
+    castling: [u64; 16],
+    en_passant: [u64; 8],
+    black_on_move: u64,
+}
+
+impl Zobrist {
+    pub const INSTANCE: Zobrist = Zobrist::new();
+
+    const SEED: u64 = 0x2545f4914f6cdd1d;
+
+    const fn new() -> Self {
+        let mut state = Self::SEED;
+        let mut pieces = [[[0u64; 64]; 6]; 2];
+        let mut color = 0;
+        while color < 2 {
+            let mut piece = 0;
+            while piece < 6 {
+                let mut square = 0;
+                while square < 64 {
+                    state = Self::next_random(state);
+                    pieces[color][piece][square] = state;
+                    square += 1;
+                }
+                piece += 1;
+            }
+            color += 1;
+        }
+
+        let mut castling = [0u64; 16];
+        let mut i = 0;
+        while i < 16 {
+            state = Self::next_random(state);
+            castling[i] = state;
+            i += 1;
+        }
+
+        let mut en_passant = [0u64; 8];
+        i = 0;
+        while i < 8 {
+            state = Self::next_random(state);
+            en_passant[i] = state;
+            i += 1;
+        }
+
+        state = Self::next_random(state);
+        return Zobrist { pieces, castling, en_passant, black_on_move: state };
+    }
+
+    // xorshift64
+    const fn next_random(mut state: u64) -> u64 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return state;
+    }
+
+    pub fn key(&self, board: &BoardState) -> u64 {
+        let mut key = self.pawn_key(board);
+        for color in [Color::White, Color::Black] {
+            for piece in [Piece::King, Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                key ^= self.pieces_key(board, color, piece);
+            }
+        }
+
+        key ^= self.castling[(board.castling.raw() & 0b1111) as usize];
+        if let Some(square) = board.en_passant_position {
+            key ^= self.en_passant[square.file()];
+        }
+
+        if board.color_on_move == Color::Black {
+            key ^= self.black_on_move;
+        }
+
+        return key;
+    }
+
+    /// Key built only from pawn placement.
+    pub fn pawn_key(&self, board: &BoardState) -> u64 {
+        return self.pieces_key(board, Color::White, Piece::Pawn)
+            ^ self.pieces_key(board, Color::Black, Piece::Pawn);
+    }
+
+    fn pieces_key(&self, board: &BoardState, color: Color, piece: Piece) -> u64 {
+        let mut key = 0;
+        let mut pieces = board.pieces[color.index()][piece.index()];
+        loop {
+            if pieces.is_empty() { break; }
+            let square = pieces.lsb();
+            pieces = pieces.remove_bit(square as u64);
+            key ^= self.pieces[color.index()][piece.index()][square];
+        }
+        return key;
+    }
+}
@@ -8,11 +8,26 @@ use chess_rot_engine::chess;
 use chess_rot_engine::chess::{BoardState, Color, Game, GameError, GameResult, Move, Piece, Square};
 use eframe::egui::{Color32, Context, Painter, Rect, Response};
 use eframe::{egui, App, Frame};
+use std::path::Path;
 use std::sync::{mpsc, Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use eframe::egui::Key::S;
 use chess_rot_engine::chess::ai::ai_strategy::{AiStrategy, Minimax, OpenAi};
+use chess_rot_engine::chess::ai::background_search::BackgroundSearch;
+use chess_rot_engine::chess::ai::endgame_evaluation::{Endgame, EndgameScore};
+use chess_rot_engine::chess::ai::endgame_tables::EndgameTables;
+use chess_rot_engine::chess::ai::evaluate::EvaluatorKind;
+use chess_rot_engine::chess::ai::evaluation_parameters::EvaluationParameters;
 use chess_rot_engine::chess::ai::evaluator::Evaluator;
+use chess_rot_engine::chess::ai::nnue::Network;
+use chess_rot_engine::chess::ai::opening_book::{BookSelection, OpeningBook};
+use chess_rot_engine::chess::ai::personality::Personality;
+use chess_rot_engine::chess::ai::piece_square_tables;
+use chess_rot_engine::chess::ai::ponder::Ponder;
+use chess_rot_engine::chess::ai::search_limits::SearchLimits;
+use chess_rot_engine::chess::ai::search_result::SearchResult;
+use chess_rot_engine::chess::ai::skill_level::SkillLevel;
+use chess_rot_engine::chess::ai::tablebase::{TablebaseProbe, Wdl};
 use chess_rot_engine::chess::Color::White;
 use chess_rot_engine::chess::move_provider::MoveProvider;
 
@@ -58,6 +73,17 @@ enum QuitState {
     Cancelled,
 }
 
+// Search on a worker thread with iterations it streams, engine moves carry the color of the engine
+struct RunningSearch {
+    search: BackgroundSearch,
+    info: mpsc::Receiver<SearchResult>,
+    color: Option<Color>,
+    // Engine ponders on the expected reply after playing its move
+    ponder: bool,
+    // Search continued after a ponder hit has no time limit of its own, it is stopped at the deadline
+    deadline: Option<Instant>,
+}
+
 struct ChessAppState {
     // game: Arc<Mutex<chess::Game>>,
     game: Game,
@@ -76,6 +102,24 @@ struct ChessAppState {
     last_move: Option<Move>,
     last_ai_move_time: Instant,
     move_completed: bool,
+    // Searched position with the result, used to show engine analysis
+    last_search: Option<(BoardState, SearchResult)>,
+    // Searches for the engine move and of the analysis run in background, so the window stays responsive
+    search: Option<RunningSearch>,
+    analysis: Option<RunningSearch>,
+    // Engines are kept between moves so their transposition tables are reused
+    engines: [Option<Minimax>; 2],
+    // Pondering engine with iterations of its search, they are shown once the expected move is played
+    ponder: Option<(Color, Ponder, mpsc::Receiver<SearchResult>)>,
+    analysis_depth: usize,
+    analysis_lines: usize,
+    // Explains evaluation of the current position
+    evaluator: Evaluator,
+    // Networks of neural evaluators, indexed by color
+    networks: [Option<Arc<Network>>; 2],
+    endgame_tables: Option<Arc<EndgameTables>>,
+    // Opening books of both players, indexed by color
+    books: [Option<Arc<OpeningBook>>; 2],
 }
 
 impl ChessAppState {
@@ -173,11 +217,287 @@ impl ChessAppState {
         self.current_fen = self.game.to_fen();
         self.on_move = self.game.current_state.on_move();
         self.last_move = None;
+        self.last_search = None;
+        if let Some((_, ponder, _)) = self.ponder.take() {
+            ponder.stop();
+        }
+        // Searches of the previous game are stopped, their results are dropped with the engines
+        for running in [self.search.take(), self.analysis.take()].into_iter().flatten() {
+            running.search.join();
+        }
+        self.engines = [None, None];
         self.last_ai_move_time = Instant::now();
         self.playing = true
     }
 
+    fn personality_settings(&mut self, ui: &mut egui::Ui, color: Color) {
+        let config = &mut self.player_config;
+        let (evaluator, network_path, personality, path) = match color {
+            Color::White => (&mut config.white_evaluator, &mut config.white_network_path,
+                             &mut config.white_personality, &mut config.white_personality_path),
+            Color::Black => (&mut config.black_evaluator, &mut config.black_network_path,
+                             &mut config.black_personality, &mut config.black_personality_path),
+        };
+
+        let mut message = None;
+        ui.label("Evaluator:");
+        egui::ComboBox::from_id_salt(ui.next_auto_id())
+            .selected_text(evaluator.name())
+            .show_ui(ui, |ui| {
+                for kind in EvaluatorKind::LIST {
+                    ui.selectable_value(evaluator, kind, kind.name());
+                }
+            });
+        if *evaluator == EvaluatorKind::Neural {
+            ui.text_edit_singleline(network_path);
+            if ui.button("Load Network").clicked() {
+                message = Some(match Network::load(Path::new(network_path.as_str())) {
+                    Ok(network) => {
+                        self.networks[color.index()] = Some(Arc::new(network));
+                        "Network loaded!".to_string()
+                    }
+                    Err(err) => err.to_string(),
+                });
+            }
+        }
+        ui.label("Personality:");
+        egui::ComboBox::from_id_salt(ui.next_auto_id())
+            .selected_text(personality.name.clone())
+            .show_ui(ui, |ui| {
+                for preset in Personality::presets() {
+                    let name = preset.name.clone();
+                    ui.selectable_value(personality, preset, name);
+                }
+            });
+        ui.label("Contempt:");
+        ui.add(egui::Slider::new(&mut personality.contempt, -200..=200));
+        ui.text_edit_singleline(path);
+
+        ui.horizontal(|ui| {
+            if ui.button("Save Personality").clicked() {
+                message = Some(match personality.save(Path::new(path.as_str())) {
+                    Ok(()) => "Personality saved!".to_string(),
+                    Err(err) => err.to_string(),
+                });
+            }
+            if ui.button("Load Personality").clicked() {
+                message = Some(match Personality::load(Path::new(path.as_str())) {
+                    Ok(loaded) => {
+                        *personality = loaded;
+                        "Personality loaded!".to_string()
+                    }
+                    Err(err) => err.to_string(),
+                });
+            }
+        });
+
+        if let Some(message) = message {
+            self.set_timed_message(&message);
+        }
+    }
+
+    fn book_settings(&mut self, ui: &mut egui::Ui, color: Color) {
+        let config = &mut self.player_config;
+        let (enabled, path, selection) = match color {
+            Color::White => (&mut config.white_book_enabled, &mut config.white_book_path, &mut config.white_book_selection),
+            Color::Black => (&mut config.black_book_enabled, &mut config.black_book_path, &mut config.black_book_selection),
+        };
+
+        let mut message = None;
+        ui.checkbox(enabled, "Opening Book");
+        if *enabled {
+            ui.text_edit_singleline(path);
+            if ui.button("Load Book").clicked() {
+                message = Some(match OpeningBook::load(Path::new(path.as_str())) {
+                    Ok(book) => {
+                        let message = format!("Opening book with {} entries loaded!", book.len());
+                        self.books[color.index()] = Some(Arc::new(book));
+                        message
+                    }
+                    Err(err) => err.to_string(),
+                });
+            }
+            egui::ComboBox::from_id_salt(ui.next_auto_id())
+                .selected_text(selection.name())
+                .show_ui(ui, |ui| {
+                    for kind in BookSelection::LIST {
+                        ui.selectable_value(selection, kind, kind.name());
+                    }
+                });
+        }
+
+        if let Some(message) = message {
+            self.set_timed_message(&message);
+        }
+    }
+
+    /// Move from the opening book of the player, when it is enabled and knows the position.
+    fn book_move(&self, color: Color) -> Option<Move> {
+        let (enabled, selection) = match color {
+            Color::White => (self.player_config.white_book_enabled, self.player_config.white_book_selection),
+            Color::Black => (self.player_config.black_book_enabled, self.player_config.black_book_selection),
+        };
+        if !enabled {
+            return None;
+        }
+
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64);
+        return self.books[color.index()].as_ref()?
+            .pick(&self.game.current_state, selection, SkillLevel::next_random(seed.max(1)));
+    }
+
+    /// Starts search of the engine move in background, `poll_searches` plays the move once it is found.
+    fn engine_move(&mut self, color: Color) {
+        if let Some(m) = self.book_move(color) {
+            println!("Making a book move {:?}", m);
+            self.last_search = None;
+            self.make_move(m);
+            return;
+        }
+
+        let (max_depth, max_time, threads, ponder, skill_level, opponent) = match color {
+            Color::White => (self.player_config.white_max_depth, self.player_config.white_max_time,
+                             self.player_config.white_threads, self.player_config.white_ponder,
+                             self.player_config.white_skill_level, self.player_config.black_player),
+            Color::Black => (self.player_config.black_max_depth, self.player_config.black_max_time,
+                             self.player_config.black_threads, self.player_config.black_ponder,
+                             self.player_config.black_skill_level, self.player_config.white_player),
+        };
+
+        let mut minimax = self.engines[color.index()].take()
+            .unwrap_or_else(|| Minimax::new(Evaluator::new(), max_depth, max_time));
+        minimax.set_threads(threads);
+        let (evaluator, personality) = match color {
+            Color::White => (self.player_config.white_evaluator, &self.player_config.white_personality),
+            Color::Black => (self.player_config.black_evaluator, &self.player_config.black_personality),
+        };
+        let params = &self.player_config.evaluation_parameters;
+        let error = match evaluator.create(personality, params, self.networks[color.index()].as_ref()) {
+            Ok(evaluator) => {
+                minimax.set_evaluator(evaluator);
+                None
+            }
+            Err(err) => {
+                let fallback = EvaluatorKind::HandCrafted.create(personality, params, None);
+                minimax.set_evaluator(fallback.expect("hand-crafted evaluator needs no network"));
+                Some(err)
+            }
+        };
+        minimax.set_contempt(personality.contempt);
+        minimax.set_tablebase(self.endgame_tables.clone().map(|t| TablebaseProbe::new(t)));
+        if let Some(err) = error {
+            self.set_timed_message(&err.to_string());
+        }
+        minimax.set_history(self.game.position_keys());
+        if minimax.skill_level().level() != skill_level {
+            let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64);
+            minimax.set_skill_level(SkillLevel::new(skill_level), seed);
+        }
+        let limits = SearchLimits {
+            depth: Some(max_depth),
+            movetime: Some((max_time * 1000.0) as u64),
+            ..SearchLimits::default()
+        };
+
+        let (sender, info) = mpsc::channel();
+        minimax.set_info_sender(sender);
+        self.search = Some(RunningSearch {
+            search: BackgroundSearch::start(minimax, &self.game.current_state, limits),
+            info,
+            color: Some(color),
+            ponder: ponder && opponent == Player::Human,
+            deadline: None,
+        });
+    }
+
+    /// Shows iterations of the running searches as they arrive and plays the engine move once its search ends.
+    fn poll_searches(&mut self, ctx: &Context) {
+        if let Some(running) = &self.analysis {
+            if let Some(info) = running.info.try_iter().last() {
+                self.last_search = Some((*running.search.board(), info));
+            }
+            if running.search.is_finished() {
+                let running = self.analysis.take().unwrap();
+                let board = *running.search.board();
+                match running.search.wait().1 {
+                    Ok(result) => self.last_search = Some((board, result)),
+                    Err(err) => self.set_timed_message(&err.to_string()),
+                }
+            }
+        }
+
+        if let Some(running) = &self.search {
+            if let Some(info) = running.info.try_iter().last() {
+                self.last_search = Some((*running.search.board(), info));
+            }
+            if running.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                running.search.stop();
+            }
+            if running.search.is_finished() {
+                let running = self.search.take().unwrap();
+                self.finish_engine_move(running);
+            }
+        }
+
+        if self.search.is_some() || self.analysis.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
+    }
+
+    fn finish_engine_move(&mut self, running: RunningSearch) {
+        let color = running.color.expect("engine move is searched for a color");
+        let board = *running.search.board();
+        let (mut minimax, result) = running.search.wait();
+        match result {
+            Ok(result) => {
+                println!("Making a move {:?}", result.best_move);
+                let m = result.best_move;
+                let ponder_move = result.pv.get(1).copied();
+                self.last_search = Some((board, result));
+                self.make_move(m);
+                match ponder_move {
+                    Some(ponder_move) if running.ponder => {
+                        minimax.set_history(self.game.position_keys());
+                        let (sender, info) = mpsc::channel();
+                        minimax.set_info_sender(sender);
+                        // Ponder search ends at the depth of the engine, the time limit applies after a ponder hit
+                        let limits = SearchLimits::depth(self.max_depth(color));
+                        let ponder = Ponder::start(minimax, &self.game.current_state, ponder_move, &limits);
+                        self.ponder = Some((color, ponder, info));
+                    }
+                    _ => self.engines[color.index()] = Some(minimax),
+                }
+            }
+            Err(err) => {
+                println!("Error: {}", err);
+                self.engines[color.index()] = Some(minimax);
+                self.set_timed_message(&err.to_string());
+            }
+        }
+    }
+
     fn make_move(&mut self, m: Move) {
+        if let Some((color, ponder, info)) = self.ponder.take() {
+            if m == ponder.ponder_move() {
+                // Ponder search goes on as the search of the engine move, a finished one is played right away
+                let max_time = match color {
+                    Color::White => self.player_config.white_max_time,
+                    Color::Black => self.player_config.black_max_time,
+                };
+                self.search = Some(RunningSearch {
+                    search: ponder.hit(),
+                    info,
+                    color: Some(color),
+                    ponder: true,
+                    deadline: Some(Instant::now() + Duration::from_secs_f32(max_time)),
+                });
+                self.set_timed_message("Ponder hit!");
+            } else {
+                // Engine keeps what it has found in transposition table
+                self.engines[color.index()] = Some(ponder.stop());
+            }
+        }
+
         if let Some(err) = self.game.make_move(m) {
             self.set_timed_message(&err.to_string());
         } else {
@@ -192,6 +512,13 @@ impl ChessAppState {
         }
     }
 
+    fn max_depth(&self, color: Color) -> usize {
+        return match color {
+            Color::White => self.player_config.white_max_depth,
+            Color::Black => self.player_config.black_max_depth,
+        };
+    }
+
     fn set_timed_message(&mut self, str: &str) {
         self.message_time = Instant::now();
         self.message = str.to_string();
@@ -210,7 +537,8 @@ impl ChessAppState {
         row: u8,
     ) {
         let piece = self.pieces[(col + row * 8) as usize];
-        if response.clicked() {
+        // Board is locked while the engine thinks, its move is searched for the current position
+        if response.clicked() && self.search.is_none() {
             x = col as i8;
             y = row as i8;
             println!("Clicked on square {} {}", col, row);
@@ -278,6 +606,17 @@ impl Default for ChessAppState {
             last_move: None,
             last_ai_move_time: Instant::now(),
             move_completed: true,
+            last_search: None,
+            search: None,
+            analysis: None,
+            engines: [None, None],
+            ponder: None,
+            analysis_depth: 4,
+            analysis_lines: 3,
+            evaluator: Evaluator::new(),
+            networks: [None, None],
+            endgame_tables: None,
+            books: [None, None],
         };
     }
 }
@@ -317,25 +656,17 @@ impl eframe::App for ChessAppState {
             }
         }
 
+        self.poll_searches(ctx);
+
         if self.game.current_state.on_move() == White {
             match self.player_config.white_player {
                 Player::Human => {}
                 Player::Minimax => {
                     if self.player_config.white_ai_start
+                        && self.search.is_none()
                         && Instant::now().duration_since(self.last_ai_move_time).as_secs() > 3 {
                         self.last_ai_move_time = Instant::now();
-                        let optimal_move = Minimax::new(Evaluator::new(), self.player_config.white_max_depth, self.player_config.white_max_time)
-                            .find_optimal_move(&self.game.current_state, &self.possible_moves);
-                        match optimal_move {
-                            Ok(m) => {
-                                println!("Making a move {:?}", m);
-                                self.make_move(m);
-                            }
-                            Err(err) => {
-                                println!("Error: {}", err);
-                                self.set_timed_message(&err.to_string());
-                            }
-                        }
+                        self.engine_move(Color::White);
                     }
                 }
                 Player::LLM => {
@@ -343,8 +674,11 @@ impl eframe::App for ChessAppState {
                         && !self.player_config.white_api_key.is_empty()
                         && Instant::now().duration_since(self.last_ai_move_time).as_secs() > 3) {
                         self.last_ai_move_time = Instant::now();
-                        let optimal_move = OpenAi::new(&self.player_config.white_api_key)
-                            .find_optimal_move(&self.game.current_state, &self.possible_moves);
+                        let optimal_move = match self.book_move(Color::White) {
+                            Some(m) => Ok(m),
+                            None => OpenAi::new(&self.player_config.white_api_key)
+                                .find_optimal_move(&self.game.current_state, &self.possible_moves),
+                        };
                         match optimal_move {
                             Ok(m) => {
                                 println!("Making a move {:?}", m);
@@ -365,20 +699,10 @@ impl eframe::App for ChessAppState {
                 Player::Human => {}
                 Player::Minimax => {
                     if self.player_config.black_ai_start
+                        && self.search.is_none()
                         && Instant::now().duration_since(self.last_ai_move_time).as_secs() > 3 {
                         self.last_ai_move_time = Instant::now();
-                        let optimal_move = Minimax::new(Evaluator::new(), self.player_config.black_max_depth, self.player_config.black_max_time)
-                            .find_optimal_move(&self.game.current_state, &self.possible_moves);
-                        match optimal_move {
-                            Ok(m) => {
-                                println!("Making a move {:?}", m);
-                                self.make_move(m);
-                            }
-                            Err(err) => {
-                                println!("Error: {}", err);
-                                self.set_timed_message(&err.to_string());
-                            }
-                        }
+                        self.engine_move(Color::Black);
                     }
                 }
                 Player::LLM => {
@@ -386,8 +710,11 @@ impl eframe::App for ChessAppState {
                         && !self.player_config.black_api_key.is_empty()
                         && Instant::now().duration_since(self.last_ai_move_time).as_secs() > 3) {
                         self.last_ai_move_time = Instant::now();
-                        let optimal_move = OpenAi::new(&self.player_config.black_api_key)
-                            .find_optimal_move(&self.game.current_state, &self.possible_moves);
+                        let optimal_move = match self.book_move(Color::Black) {
+                            Some(m) => Ok(m),
+                            None => OpenAi::new(&self.player_config.black_api_key)
+                                .find_optimal_move(&self.game.current_state, &self.possible_moves),
+                        };
                         match optimal_move {
                             Ok(m) => {
                                 println!("Making a move {:?}", m);
@@ -482,6 +809,14 @@ impl eframe::App for ChessAppState {
                     ui.add(egui::Slider::new(&mut self.player_config.white_max_depth, 1..=8));
                     ui.label("Max Search Time:");
                     ui.add(egui::Slider::new(&mut self.player_config.white_max_time, 1.0..=20.0));
+                    ui.label("Threads:");
+                    ui.add(egui::Slider::new(&mut self.player_config.white_threads, 1..=8));
+                    ui.checkbox(&mut self.player_config.white_ponder, "Ponder");
+                    let elo = SkillLevel::new(self.player_config.white_skill_level).elo();
+                    ui.label(format!("Skill Level (~{} Elo):", elo));
+                    ui.add(egui::Slider::new(&mut self.player_config.white_skill_level, 0..=SkillLevel::MAX));
+                    self.personality_settings(ui, Color::White);
+                    self.book_settings(ui, Color::White);
 
                     ui.text_edit_singleline(&mut self.player_config.white_api_key);
                     let start_pause_text = if self.player_config.white_ai_start {
@@ -493,6 +828,7 @@ impl eframe::App for ChessAppState {
                 } else if self.player_config.white_player == Player::LLM {
                     ui.label("OpenAI API Key:");
                     ui.text_edit_singleline(&mut self.player_config.white_api_key);
+                    self.book_settings(ui, Color::White);
                     let start_pause_text = if self.player_config.white_ai_start {
                         "Pause"
                     } else { "Start" };
@@ -535,6 +871,14 @@ impl eframe::App for ChessAppState {
                     ui.add(egui::Slider::new(&mut self.player_config.black_max_depth, 1..=8));
                     ui.label("Max Search Time:");
                     ui.add(egui::Slider::new(&mut self.player_config.black_max_time, 1.0..=20.0));
+                    ui.label("Threads:");
+                    ui.add(egui::Slider::new(&mut self.player_config.black_threads, 1..=8));
+                    ui.checkbox(&mut self.player_config.black_ponder, "Ponder");
+                    let elo = SkillLevel::new(self.player_config.black_skill_level).elo();
+                    ui.label(format!("Skill Level (~{} Elo):", elo));
+                    ui.add(egui::Slider::new(&mut self.player_config.black_skill_level, 0..=SkillLevel::MAX));
+                    self.personality_settings(ui, Color::Black);
+                    self.book_settings(ui, Color::Black);
                     let start_pause_text = if self.player_config.black_ai_start {
                         "Pause"
                     } else { "Start" };
@@ -544,6 +888,7 @@ impl eframe::App for ChessAppState {
                 } else if self.player_config.black_player == Player::LLM {
                     ui.label("OpenAI API Key:");
                     ui.text_edit_singleline(&mut self.player_config.black_api_key);
+                    self.book_settings(ui, Color::Black);
                     let start_pause_text = if self.player_config.black_ai_start {
                         "Pause"
                     } else { "Start" };
@@ -567,6 +912,134 @@ impl eframe::App for ChessAppState {
                     ui.label(format!("PLY: {}", self.game.current_state.ply()));
                     // }
                     ui.label(format!("Current FEN: {}", self.current_fen));
+
+                    ui.separator();
+                    ui.heading("Engine Analysis:");
+                    ui.label("Analysis Depth:");
+                    ui.add(egui::Slider::new(&mut self.analysis_depth, 1..=8));
+                    ui.label("Lines:");
+                    ui.add(egui::Slider::new(&mut self.analysis_lines, 1..=self.possible_moves.len().max(1)));
+                    if let Some(running) = &self.analysis {
+                        // Result of the stopped analysis is picked up by poll_searches
+                        if ui.button("Stop Analysis").clicked() {
+                            running.search.stop();
+                        }
+                    } else if ui.button("Analyse Position").clicked() {
+                        let evaluator = Evaluator::with_parameters(self.player_config.evaluation_parameters.clone());
+                        let mut minimax = Minimax::new(evaluator, self.analysis_depth, 0.0);
+                        minimax.set_multi_pv(self.analysis_lines);
+                        let (sender, info) = mpsc::channel();
+                        minimax.set_info_sender(sender);
+                        let limits = SearchLimits::depth(self.analysis_depth);
+                        self.analysis = Some(RunningSearch {
+                            search: BackgroundSearch::start(minimax, &self.game.current_state, limits),
+                            info,
+                            color: None,
+                            ponder: false,
+                            deadline: None,
+                        });
+                    }
+                    if let Some(color) = self.search.as_ref().and_then(|running| running.color) {
+                        ui.label(format!("{} is thinking...", color));
+                    }
+
+                    if let Some((board, result)) = &self.last_search {
+                        ui.label(format!("Depth: {}/{}", result.depth, result.seldepth));
+                        ui.label(format!("Nodes: {} ({} nps)", result.nodes, result.nps));
+                        ui.label(format!("Hash: {}‰", result.hashfull));
+                        for (i, line) in result.lines.iter().enumerate() {
+                            ui.label(format!("{}. ({}) {}", i + 1, line.score, line.to_san(board).join(" ")));
+                        }
+                    }
+
+                    ui.label("Evaluation Parameters:");
+                    ui.text_edit_singleline(&mut self.player_config.evaluation_parameters_path);
+                    ui.horizontal(|ui| {
+                        let path = Path::new(&self.player_config.evaluation_parameters_path).to_path_buf();
+                        if ui.button("Load Parameters").clicked() {
+                            let message = match EvaluationParameters::load(&path) {
+                                Ok(params) => {
+                                    self.evaluator = Evaluator::with_parameters(params.clone());
+                                    self.player_config.evaluation_parameters = params;
+                                    "Parameters loaded!".to_string()
+                                }
+                                Err(err) => err.to_string(),
+                            };
+                            self.set_timed_message(&message);
+                        }
+                        if ui.button("Dump Parameters").clicked() {
+                            let message = match self.player_config.evaluation_parameters.save(&path) {
+                                Ok(()) => "Parameters dumped!".to_string(),
+                                Err(err) => err.to_string(),
+                            };
+                            self.set_timed_message(&message);
+                        }
+                    });
+
+                    egui::CollapsingHeader::new("Evaluation Breakdown").show(ui, |ui| {
+                        let breakdown = self.evaluator.explain(&self.game.current_state);
+                        egui::Grid::new("evaluation_breakdown").striped(true).show(ui, |ui| {
+                            for header in ["Term", "White MG", "White EG", "Black MG", "Black EG"] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            let black = breakdown.sides[Color::Black.index()].terms();
+                            for ((name, white), (_, black)) in breakdown.sides[Color::White.index()].terms().iter().zip(black.iter()) {
+                                ui.label(*name);
+                                for value in [white.middle_game, white.end_game, black.middle_game, black.end_game] {
+                                    ui.label(value.to_string());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                        ui.label(format!("Phase: {}/{}", breakdown.phase, piece_square_tables::MAX_PHASE));
+                        ui.label(format!("Risk: {}", breakdown.risk));
+                        match breakdown.endgame {
+                            Some((endgame, EndgameScore::Exact(score))) => { ui.label(format!("Endgame: {} (exact {:+.2})", endgame, score as f32 / 100.0)); }
+                            Some((endgame, EndgameScore::Scale(scale))) => { ui.label(format!("Endgame: {} (scale {}/{})", endgame, scale, Endgame::SCALE_NORMAL)); }
+                            None => {}
+                        }
+                        ui.label(format!("Total: {:+.2} (white's perspective)", breakdown.total() as f32 / 100.0));
+                    });
+
+                    ui.label("Endgame Tables:");
+                    ui.text_edit_singleline(&mut self.player_config.endgame_tables_path);
+                    if ui.button("Load Tables").clicked() {
+                        let message = match EndgameTables::load_dir(Path::new(&self.player_config.endgame_tables_path)) {
+                            Ok(tables) => {
+                                let message = format!("{} endgame tables loaded!", tables.materials().len());
+                                self.endgame_tables = Some(Arc::new(tables));
+                                message
+                            }
+                            Err(err) => err.to_string(),
+                        };
+                        self.set_timed_message(&message);
+                    }
+
+                    egui::CollapsingHeader::new("Endgame Trainer").show(ui, |ui| {
+                        let Some(tables) = &self.endgame_tables else {
+                            ui.label("No endgame tables loaded");
+                            return;
+                        };
+                        let board = self.game.current_state;
+                        let Some((wdl, plies)) = tables.probe(&board) else {
+                            ui.label("Position is not in the tables");
+                            return;
+                        };
+
+                        let side = if board.on_move() == White { "White" } else { "Black" };
+                        ui.label(match wdl {
+                            Wdl::Win => format!("{} mates in {} moves", side, (plies + 1) / 2),
+                            Wdl::Draw => "Draw with best play".to_string(),
+                            Wdl::Loss if plies == 0 => format!("{} is checkmated", side),
+                            Wdl::Loss => format!("{} is mated in {} moves", side, plies / 2),
+                        });
+                        let legal_moves = MoveProvider::INSTANCE.legal_moves(&board);
+                        if let Some(best) = TablebaseProbe::new(tables.clone()).root_moves(&board, &legal_moves) {
+                            let san: Vec<String> = best.iter().map(|m| board.to_san(*m)).collect();
+                            ui.label(format!("Best moves: {}", san.join(", ")));
+                        }
+                    });
                 }
 
                 ui.separator();
@@ -1,4 +1,9 @@
 use crate::chess::ai::AiMoveProvider;
+use chess_rot_engine::chess::ai::evaluate::EvaluatorKind;
+use chess_rot_engine::chess::ai::evaluation_parameters::EvaluationParameters;
+use chess_rot_engine::chess::ai::opening_book::BookSelection;
+use chess_rot_engine::chess::ai::personality::Personality;
+use chess_rot_engine::chess::ai::skill_level::SkillLevel;
 use crate::player::Player::Human;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -16,6 +21,34 @@ pub struct PlayerConfig {
     pub black_max_depth: usize,
     pub white_max_time: f32,
     pub black_max_time: f32,
+    pub white_threads: usize,
+    pub black_threads: usize,
+    pub white_ponder: bool,
+    pub black_ponder: bool,
+    pub white_skill_level: u8,
+    pub black_skill_level: u8,
+    pub white_evaluator: EvaluatorKind,
+    pub black_evaluator: EvaluatorKind,
+    // Network file of the neural evaluator
+    pub white_network_path: String,
+    pub black_network_path: String,
+    pub white_personality: Personality,
+    pub black_personality: Personality,
+    // File the personality is saved to and loaded from
+    pub white_personality_path: String,
+    pub black_personality_path: String,
+    // Shared by hand-crafted evaluators of both players, weights come from their personalities
+    pub evaluation_parameters: EvaluationParameters,
+    pub evaluation_parameters_path: String,
+    // Directory with endgame tables, used by both players and the endgame trainer
+    pub endgame_tables_path: String,
+    // Polyglot opening book played from before any search or LLM request
+    pub white_book_enabled: bool,
+    pub black_book_enabled: bool,
+    pub white_book_path: String,
+    pub black_book_path: String,
+    pub white_book_selection: BookSelection,
+    pub black_book_selection: BookSelection,
     pub white_api_key: String,
     pub black_api_key: String,
     pub white_ai_start: bool,
@@ -31,6 +64,29 @@ impl Default for PlayerConfig {
             black_max_depth: 5,
             white_max_time: 5.0,
             black_max_time: 5.0,
+            white_threads: 1,
+            black_threads: 1,
+            white_ponder: false,
+            black_ponder: false,
+            white_skill_level: SkillLevel::MAX,
+            black_skill_level: SkillLevel::MAX,
+            white_evaluator: EvaluatorKind::HandCrafted,
+            black_evaluator: EvaluatorKind::HandCrafted,
+            white_network_path: "white.nnue".to_string(),
+            black_network_path: "black.nnue".to_string(),
+            white_personality: Personality::balanced(),
+            black_personality: Personality::balanced(),
+            white_personality_path: "white_personality.toml".to_string(),
+            black_personality_path: "black_personality.toml".to_string(),
+            evaluation_parameters: EvaluationParameters::default(),
+            evaluation_parameters_path: "evaluation.toml".to_string(),
+            endgame_tables_path: "tablebases".to_string(),
+            white_book_enabled: false,
+            black_book_enabled: false,
+            white_book_path: "book.bin".to_string(),
+            black_book_path: "book.bin".to_string(),
+            white_book_selection: BookSelection::WeightedRandom,
+            black_book_selection: BookSelection::WeightedRandom,
             white_api_key: "".to_string(),
             black_api_key: "".to_string(),
             white_ai_start: false,